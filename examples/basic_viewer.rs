@@ -7,7 +7,7 @@
 
 use std::path::PathBuf;
 use showme::{Config, Renderer, BackendKind, RenderSizing};
-use showme::config::{PixelationMode, RotationMode, BackgroundColor};
+use showme::config::{PixelationMode, RotationMode, BackgroundColor, OutputFormat};
 
 fn main() -> showme::Result<()> {
     // Get image path from command line arguments
@@ -48,6 +48,7 @@ fn main() -> showme::Result<()> {
         frame_offset: 0,
         max_duration: None,
         auto_crop: false,
+        auto_crop_tolerance: 10,
         crop_border: 0,
         use_8bit_color: false,
         output_file: None,
@@ -59,6 +60,24 @@ fn main() -> showme::Result<()> {
         scroll_delay: std::time::Duration::from_millis(50),
         scroll_dx: 1,
         scroll_dy: 1,
+        term_size: None,
+        cell_size: None,
+        output_format: OutputFormat::Ansi,
+        record: None,
+        decode_limits: showme::DecodeLimits::default(),
+        tone_map: showme::ToneMapOperator::Aces,
+        exposure: 0.0,
+        color_management: true,
+        brightness: 0.0,
+        contrast: 0.0,
+        gamma: 1.0,
+        saturation: 1.0,
+        grayscale: false,
+        invert: false,
+        sharpen: None,
+        flip: None,
+        crop: None,
+        pages: None,
     };
 
     // Build and run the renderer
@@ -8,7 +8,7 @@
 use std::path::PathBuf;
 use std::time::Duration;
 use showme::{Config, Renderer, BackendKind, RenderSizing};
-use showme::config::{PixelationMode, RotationMode, BackgroundColor};
+use showme::config::{PixelationMode, RotationMode, BackgroundColor, OutputFormat};
 
 fn main() -> showme::Result<()> {
     let args: Vec<String> = std::env::args().collect();
@@ -38,6 +38,8 @@ fn main() -> showme::Result<()> {
             upscale: false,
             upscale_integer: false,
             antialias: true,
+            filter: None,
+            linear_light: false,
             width_stretch: 2.0, // Typical terminal chars are ~2x taller than wide, stretch by 2x
         },
 
@@ -59,6 +61,7 @@ fn main() -> showme::Result<()> {
 
         // Image processing
         auto_crop: false,
+        auto_crop_tolerance: 10,
         crop_border: 0,
 
         // Advanced options
@@ -82,6 +85,24 @@ fn main() -> showme::Result<()> {
         scroll_delay: Duration::from_millis(50),
         scroll_dx: 1,
         scroll_dy: 1,
+        term_size: None,
+        cell_size: None,
+        output_format: OutputFormat::Ansi,
+        record: None,
+        decode_limits: showme::DecodeLimits::default(),
+        tone_map: showme::ToneMapOperator::Aces,
+        exposure: 0.0,
+        color_management: true,
+        brightness: 0.0,
+        contrast: 0.0,
+        gamma: 1.0,
+        saturation: 1.0,
+        grayscale: false,
+        invert: false,
+        sharpen: None,
+        flip: None,
+        crop: None,
+        pages: None,
 
         // Force type interpretation
         force_video: false,
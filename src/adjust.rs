@@ -0,0 +1,135 @@
+//! Colour and detail adjustments applied after orientation and cropping
+//!
+//! Dark screenshots and washed-out scans lose most of their detail once
+//! they are squeezed into a few dozen terminal cells. The adjustments run in
+//! a fixed order: brightness, contrast and gamma (one lookup table), then
+//! saturation, grayscale, invert and finally the unsharp mask, so that
+//! sharpening sees the colours that will actually be shown.
+
+use image::{Rgba, RgbaImage, imageops};
+
+use crate::config::UnsharpMask;
+
+/// The adjustments to apply; the default changes nothing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Adjustments {
+    /// Added to every channel, in percent of full scale (-100 to 100)
+    pub brightness: f32,
+    /// Contrast change around mid-gray in percent; -100 flattens to gray
+    pub contrast: f32,
+    /// Gamma correction; above 1 lifts shadows, below 1 darkens them
+    pub gamma: f32,
+    /// Saturation factor; 0 is gray, 1 unchanged, 2 doubles colourfulness
+    pub saturation: f32,
+    pub grayscale: bool,
+    pub invert: bool,
+    pub sharpen: Option<UnsharpMask>,
+}
+
+impl Default for Adjustments {
+    fn default() -> Self {
+        Self {
+            brightness: 0.0,
+            contrast: 0.0,
+            gamma: 1.0,
+            saturation: 1.0,
+            grayscale: false,
+            invert: false,
+            sharpen: None,
+        }
+    }
+}
+
+impl Adjustments {
+    /// Whether applying these would leave every image unchanged
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Apply every adjustment to `image`
+    pub fn apply(&self, mut image: RgbaImage) -> RgbaImage {
+        if self.is_identity() {
+            return image;
+        }
+
+        let levels = self.levels();
+        let saturation = if self.grayscale { 0.0 } else { self.saturation };
+        for pixel in image.pixels_mut() {
+            let [r, g, b, a] = pixel.0;
+            let mut rgb = [levels[r as usize], levels[g as usize], levels[b as usize]];
+            if saturation != 1.0 {
+                rgb = saturate(rgb, saturation);
+            }
+            if self.invert {
+                rgb = rgb.map(|c| 255 - c);
+            }
+            *pixel = Rgba([rgb[0], rgb[1], rgb[2], a]);
+        }
+
+        if let Some(mask) = self.sharpen {
+            image = imageops::unsharpen(&image, mask.sigma, mask.threshold);
+        }
+        image
+    }
+
+    /// Brightness, contrast and gamma folded into one table per channel value
+    fn levels(&self) -> [u8; 256] {
+        let offset = self.brightness.clamp(-100.0, 100.0) / 100.0;
+        let contrast = (100.0 + self.contrast.clamp(-100.0, f32::MAX)) / 100.0;
+        let exponent = if self.gamma > 0.0 && self.gamma.is_finite() { 1.0 / self.gamma } else { 1.0 };
+        std::array::from_fn(|value| {
+            let mut level = value as f32 / 255.0 + offset;
+            level = (level - 0.5) * contrast + 0.5;
+            level = level.clamp(0.0, 1.0).powf(exponent);
+            (level * 255.0).round() as u8
+        })
+    }
+}
+
+/// Move each channel towards (or away from) the pixel's Rec. 709 luma
+fn saturate(rgb: [u8; 3], factor: f32) -> [u8; 3] {
+    let [r, g, b] = rgb.map(f32::from);
+    let luma = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    [r, g, b].map(|c| (luma + (c - luma) * factor).round().clamp(0.0, 255.0) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(adjustments: Adjustments, rgba: [u8; 4]) -> [u8; 4] {
+        adjustments.apply(RgbaImage::from_pixel(1, 1, Rgba(rgba))).get_pixel(0, 0).0
+    }
+
+    #[test]
+    fn adjusts_levels_and_colour() {
+        assert!(Adjustments::default().is_identity());
+        assert_eq!(pixel(Adjustments::default(), [10, 20, 30, 40]), [10, 20, 30, 40]);
+
+        let brighter = Adjustments { brightness: 20.0, ..Adjustments::default() };
+        assert_eq!(pixel(brighter, [0, 100, 250, 255]), [51, 151, 255, 255]);
+
+        let flat = Adjustments { contrast: -100.0, ..Adjustments::default() };
+        assert_eq!(pixel(flat, [0, 100, 255, 7]), [128, 128, 128, 7]);
+
+        let lifted = Adjustments { gamma: 2.0, ..Adjustments::default() };
+        assert_eq!(pixel(lifted, [0, 64, 255, 255]), [0, 128, 255, 255]);
+
+        let gray = Adjustments { grayscale: true, invert: true, ..Adjustments::default() };
+        let [r, g, b, a] = pixel(gray, [255, 0, 0, 255]);
+        assert_eq!((r, a), (201, 255));
+        assert!(r == g && g == b);
+    }
+
+    #[test]
+    fn sharpens_edges() {
+        let edge = RgbaImage::from_fn(8, 1, |x, _| if x < 4 { Rgba([100, 100, 100, 255]) } else { Rgba([150, 150, 150, 255]) });
+        let sharpen = Adjustments {
+            sharpen: Some(UnsharpMask { sigma: 1.0, threshold: 0 }),
+            ..Adjustments::default()
+        };
+        let sharpened = sharpen.apply(edge);
+        assert!(sharpened.get_pixel(3, 0)[0] < 100);
+        assert!(sharpened.get_pixel(4, 0)[0] > 150);
+    }
+}
@@ -0,0 +1,83 @@
+//! Colour state tracking for the escape sequences the renderers emit
+//!
+//! Shared by the exporters that turn terminal output into something else
+//! (HTML pages, PNG screenshots) so they agree on how SGR codes map to
+//! colours.
+
+use crate::color_quantize::color_256_to_rgb;
+
+/// Foreground and background colour selected by SGR codes; `None` is the terminal default
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct SgrState {
+    pub fg: Option<[u8; 3]>,
+    pub bg: Option<[u8; 3]>,
+}
+
+impl SgrState {
+    /// Apply the `;`-separated parameters of a `CSI ... m` sequence
+    pub fn apply(&mut self, params: &str) {
+        let codes: Vec<u16> = params
+            .split(';')
+            .map(|code| code.parse().unwrap_or(0))
+            .collect();
+
+        let mut iter = codes.iter().copied();
+        while let Some(code) = iter.next() {
+            match code {
+                0 => *self = SgrState::default(),
+                30..=37 => self.fg = Some(palette(code as u8 - 30)),
+                90..=97 => self.fg = Some(palette(code as u8 - 90 + 8)),
+                40..=47 => self.bg = Some(palette(code as u8 - 40)),
+                100..=107 => self.bg = Some(palette(code as u8 - 100 + 8)),
+                39 => self.fg = None,
+                49 => self.bg = None,
+                38 | 48 => {
+                    let color = match iter.next() {
+                        Some(2) => match (iter.next(), iter.next(), iter.next()) {
+                            (Some(r), Some(g), Some(b)) => Some([r as u8, g as u8, b as u8]),
+                            _ => None,
+                        },
+                        Some(5) => iter.next().map(|index| palette(index as u8)),
+                        _ => None,
+                    };
+                    if code == 38 {
+                        self.fg = color;
+                    } else {
+                        self.bg = color;
+                    }
+                }
+                // Bold, underline and friends are never emitted by the renderers
+                _ => {}
+            }
+        }
+    }
+}
+
+/// RGB for an xterm-256 palette index, including the 16 system colours
+pub(crate) fn palette(index: u8) -> [u8; 3] {
+    const SYSTEM: [[u8; 3]; 16] = [
+        [0, 0, 0],
+        [205, 0, 0],
+        [0, 205, 0],
+        [205, 205, 0],
+        [0, 0, 238],
+        [205, 0, 205],
+        [0, 205, 205],
+        [229, 229, 229],
+        [127, 127, 127],
+        [255, 0, 0],
+        [0, 255, 0],
+        [255, 255, 0],
+        [92, 92, 255],
+        [255, 0, 255],
+        [0, 255, 255],
+        [255, 255, 255],
+    ];
+    match index {
+        0..=15 => SYSTEM[index as usize],
+        _ => {
+            let rgb = color_256_to_rgb(index);
+            [rgb.r, rgb.g, rgb.b]
+        }
+    }
+}
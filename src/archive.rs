@@ -0,0 +1,211 @@
+//! Zip, comic-book and tar archives treated as image containers
+//!
+//! Every image entry is read into memory in archive order and later decoded
+//! as its own [`ImageSequence`](crate::image::ImageSequence). Directories,
+//! hidden entries, macOS resource forks and files the raster decoders don't
+//! understand are skipped.
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use image::ImageFormat;
+
+use crate::error::{Limit, Result, RimgError};
+
+/// Most bytes reserved up front from an entry's declared size; larger
+/// entries grow the buffer as they are actually read
+const MAX_PREALLOCATION: u64 = 1 << 20;
+
+/// One image entry extracted from an archive
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    /// Path of the entry inside the archive
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+}
+
+impl ArchiveKind {
+    fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "zip" | "cbz" => Some(Self::Zip),
+            "tar" | "cbt" => Some(Self::Tar),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `path` looks like an archive the loader can open
+pub fn is_archive(path: &Path) -> bool {
+    ArchiveKind::from_path(path).is_some()
+}
+
+/// Read every image entry of the archive at `path`, in archive order
+pub fn read_image_entries(path: &Path) -> Result<Vec<ArchiveEntry>> {
+    read_image_entries_with(path, None)
+}
+
+/// [`read_image_entries`], refusing entries that decompress to more than `max_entry_bytes`
+pub fn read_image_entries_with(path: &Path, max_entry_bytes: Option<u64>) -> Result<Vec<ArchiveEntry>> {
+    let kind = ArchiveKind::from_path(path)
+        .ok_or_else(|| RimgError::other(format!("'{}' is not a supported archive", path.display())))?;
+    let file = File::open(path)?;
+    let invalid = |err: &dyn std::fmt::Display| {
+        RimgError::other(format!("failed to read archive '{}': {}", path.display(), err))
+    };
+
+    let mut entries = Vec::new();
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|err| invalid(&err))?;
+            for index in 0..archive.len() {
+                let mut entry = archive.by_index(index).map_err(|err| invalid(&err))?;
+                if !entry.is_file() || !is_image_entry(entry.name()) {
+                    continue;
+                }
+                let (name, size) = (entry.name().to_string(), entry.size());
+                let data = read_entry(&mut entry, size, max_entry_bytes, path, &name)?;
+                entries.push(ArchiveEntry { name, data });
+            }
+        }
+        ArchiveKind::Tar => {
+            let mut archive = tar::Archive::new(BufReader::new(file));
+            for entry in archive.entries().map_err(|err| invalid(&err))? {
+                let mut entry = entry.map_err(|err| invalid(&err))?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+                let name = entry.path().map_err(|err| invalid(&err))?.to_string_lossy().into_owned();
+                if !is_image_entry(&name) {
+                    continue;
+                }
+                let size = entry.size();
+                let data = read_entry(&mut entry, size, max_entry_bytes, path, &name)?;
+                entries.push(ArchiveEntry { name, data });
+            }
+        }
+    }
+
+    if entries.is_empty() {
+        return Err(RimgError::other(format!(
+            "archive '{}' contains no images",
+            path.display()
+        )));
+    }
+    Ok(entries)
+}
+
+/// Read one entry, trusting neither its declared size nor its compression ratio
+fn read_entry(
+    entry: &mut impl Read,
+    declared: u64,
+    max_bytes: Option<u64>,
+    archive: &Path,
+    name: &str,
+) -> Result<Vec<u8>> {
+    let max = max_bytes.unwrap_or(u64::MAX);
+    let exceeded = || RimgError::LimitExceeded {
+        path: PathBuf::from(format!("{}:{}", archive.display(), name)),
+        limit: Limit::Bytes(max),
+    };
+    if declared > max {
+        return Err(exceeded());
+    }
+
+    let mut data = Vec::with_capacity(declared.min(MAX_PREALLOCATION) as usize);
+    entry
+        .take(max.saturating_add(1))
+        .read_to_end(&mut data)
+        .map_err(|err| RimgError::other(format!("failed to read archive '{}': {}", archive.display(), err)))?;
+    if data.len() as u64 > max {
+        return Err(exceeded());
+    }
+    Ok(data)
+}
+
+fn is_image_entry(name: &str) -> bool {
+    let mut components = name.split('/').filter(|part| !part.is_empty());
+    if components.any(|part| part.starts_with('.') || part == "__MACOSX") {
+        return false;
+    }
+    Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(ImageFormat::from_extension)
+        .is_some_and(|format| format.reading_enabled())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempPath;
+    use std::io::{Cursor, Write};
+
+    fn zip_of(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut buffer = Cursor::new(Vec::new());
+        {
+            let mut writer = zip::ZipWriter::new(&mut buffer);
+            let options = zip::write::SimpleFileOptions::default();
+            for (name, data) in entries {
+                writer.start_file(*name, options).expect("start entry");
+                writer.write_all(data).expect("write entry");
+            }
+            writer.finish().expect("finish zip");
+        }
+        buffer.into_inner()
+    }
+
+    #[test]
+    fn filters_hidden_and_non_image_entries() {
+        assert!(is_image_entry("page01.png"));
+        assert!(is_image_entry("chapter1/Page02.JPG"));
+        assert!(!is_image_entry("ComicInfo.xml"));
+        assert!(!is_image_entry("__MACOSX/._page01.png"));
+        assert!(!is_image_entry(".thumbs/page01.png"));
+    }
+
+    #[test]
+    fn reads_zip_entries_in_archive_order() {
+        let path = TempPath::new("archive.cbz");
+        std::fs::write(&path, zip_of(&[("page10.png", b"ten"), ("notes.txt", b"txt"), ("page02.png", b"two")]))
+            .expect("write archive");
+        let entries = read_image_entries(&path).expect("read archive");
+
+        let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["page10.png", "page02.png"]);
+        assert_eq!(entries[1].data, b"two");
+    }
+
+    #[test]
+    fn load_image_reads_the_first_image_entry() {
+        let png = |width| {
+            let mut data = Cursor::new(Vec::new());
+            image::RgbaImage::new(width, 1)
+                .write_to(&mut data, ImageFormat::Png)
+                .expect("encode png");
+            data.into_inner()
+        };
+        let path = TempPath::new("first.zip");
+        std::fs::write(&path, zip_of(&[("notes.txt", b"txt"), ("a.png", &png(3)), ("b.png", &png(5))]))
+            .expect("write archive");
+
+        let sequence = crate::image::load_image_with(&path, &Default::default()).expect("load first entry");
+        assert_eq!(sequence.frames[0].pixels.width(), 3);
+        assert!(sequence.path.to_string_lossy().ends_with("first.zip:a.png"));
+    }
+
+    #[test]
+    fn ignores_forged_entry_sizes_without_a_limit() {
+        let data = read_entry(&mut Cursor::new(b"tiny".to_vec()), u64::MAX, None, Path::new("a.tar"), "x.png")
+            .expect("read entry");
+        assert_eq!(data, b"tiny");
+        assert!(data.capacity() <= MAX_PREALLOCATION as usize);
+    }
+}
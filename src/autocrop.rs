@@ -3,11 +3,15 @@
 /// This module implements automatic border detection and cropping,
 /// useful for screenshots, scanned documents, and images with padding.
 
+use std::collections::HashMap;
+
 use image::{DynamicImage, GenericImageView, Rgba};
 
-/// Threshold for color similarity (0-255 per channel)
+use crate::config::CropRegion;
+
+/// Default threshold for color similarity (0-255 per channel)
 /// Pixels within this threshold of the border color are considered background
-const COLOR_THRESHOLD: u8 = 10;
+pub const DEFAULT_TOLERANCE: u8 = 10;
 
 /// Determine if two colors are similar within threshold
 fn colors_similar(a: Rgba<u8>, b: Rgba<u8>, threshold: u8) -> bool {
@@ -18,105 +22,135 @@ fn colors_similar(a: Rgba<u8>, b: Rgba<u8>, threshold: u8) -> bool {
         && (a[3] as i32 - b[3] as i32).abs() <= threshold
 }
 
-/// Detect the background color by sampling edge pixels
+/// Whether `pixel` belongs to the border of color `bg`
 ///
-/// Samples the four corners and edges to determine the most common
-/// edge color, which is assumed to be the background.
-fn detect_background_color(img: &DynamicImage) -> Rgba<u8> {
-    let (width, height) = img.dimensions();
-    if width == 0 || height == 0 {
-        return Rgba([255, 255, 255, 255]);
+/// Fully transparent pixels carry arbitrary RGB values, so against a
+/// transparent background only their alpha counts.
+fn is_background(pixel: Rgba<u8>, bg: Rgba<u8>, tolerance: u8) -> bool {
+    if bg[3] == 0 {
+        return pixel[3] == 0;
     }
-
-    // Sample corner pixel (most likely to be background)
-    // Use top-left corner as the background color
-    // (most common convention for padding)
-    img.get_pixel(0, 0)
+    colors_similar(pixel, bg, tolerance)
 }
 
-/// Find the crop boundaries by scanning inward from edges
+/// Most edge colors tried as the background; the rest still vote
+const MAX_CANDIDATES: usize = 256;
+
+/// Detect the background color by sampling edge pixels
 ///
-/// Returns (left, top, right, bottom) boundaries
-fn find_crop_bounds(img: &DynamicImage, bg_color: Rgba<u8>) -> Option<(u32, u32, u32, u32)> {
+/// Every pixel on the four edges votes for each candidate color it lies
+/// within `tolerance` of, and the candidate with the most votes wins. JPEG
+/// and scanner noise spreads a border over many near-identical colors,
+/// which still add up, so a logo touching one edge can't outvote it. Ties
+/// go to the color of the top-left pixel.
+fn detect_background_color<I: GenericImageView<Pixel = Rgba<u8>>>(img: &I, tolerance: u8) -> Rgba<u8> {
     let (width, height) = img.dimensions();
     if width == 0 || height == 0 {
-        return None;
-    }
-
-    // Scan from left
-    let mut left = 0;
-    'left_scan: for x in 0..width {
-        for y in 0..height {
-            if !colors_similar(img.get_pixel(x, y), bg_color, COLOR_THRESHOLD) {
-                left = x;
-                break 'left_scan;
-            }
-        }
-    }
-
-    // Scan from right
-    let mut right = width - 1;
-    'right_scan: for x in (0..width).rev() {
-        for y in 0..height {
-            if !colors_similar(img.get_pixel(x, y), bg_color, COLOR_THRESHOLD) {
-                right = x;
-                break 'right_scan;
-            }
-        }
+        return Rgba([255, 255, 255, 255]);
     }
 
-    // Scan from top
-    let mut top = 0;
-    'top_scan: for y in 0..height {
-        for x in 0..width {
-            if !colors_similar(img.get_pixel(x, y), bg_color, COLOR_THRESHOLD) {
-                top = y;
-                break 'top_scan;
-            }
-        }
+    // All fully transparent pixels vote for the same color
+    let vote_color = |pixel: Rgba<u8>| if pixel[3] == 0 { Rgba([0, 0, 0, 0]) } else { pixel };
+    let mut counts: HashMap<Rgba<u8>, usize> = HashMap::new();
+    let horizontal = (0..width).flat_map(|x| [(x, 0), (x, height - 1)]);
+    let vertical = (1..height.saturating_sub(1)).flat_map(|y| [(0, y), (width - 1, y)]);
+    for (x, y) in horizontal.chain(vertical) {
+        *counts.entry(vote_color(img.get_pixel(x, y))).or_default() += 1;
     }
+    let mut colors: Vec<(Rgba<u8>, usize)> = counts.into_iter().collect();
+    colors.sort_unstable_by_key(|&(color, count)| (std::cmp::Reverse(count), color.0));
+
+    // The most common exact colors, then an even spread of the rest
+    let corner = vote_color(img.get_pixel(0, 0));
+    let step = colors.len().div_ceil(MAX_CANDIDATES).max(1);
+    let candidates = colors
+        .iter()
+        .take(MAX_CANDIDATES / 2)
+        .chain(colors.iter().step_by(step))
+        .map(|&(color, _)| color)
+        .chain(std::iter::once(corner));
+
+    let votes = |candidate: Rgba<u8>| -> usize {
+        colors
+            .iter()
+            .filter(|&&(color, _)| is_background(color, candidate, tolerance))
+            .map(|&(_, count)| count)
+            .sum()
+    };
+    candidates
+        .map(|candidate| (votes(candidate), candidate))
+        .max_by_key(|&(count, color)| (count, color == corner, color.0))
+        .map_or(corner, |(_, color)| color)
+}
 
-    // Scan from bottom
-    let mut bottom = height - 1;
-    'bottom_scan: for y in (0..height).rev() {
-        for x in 0..width {
-            if !colors_similar(img.get_pixel(x, y), bg_color, COLOR_THRESHOLD) {
-                bottom = y;
-                break 'bottom_scan;
-            }
-        }
-    }
+/// Find the content boundaries by scanning inward from edges
+///
+/// Returns inclusive (left, top, right, bottom) boundaries, or `None` when
+/// every pixel is background.
+fn find_crop_bounds<I: GenericImageView<Pixel = Rgba<u8>>>(
+    img: &I,
+    bg_color: Rgba<u8>,
+    tolerance: u8,
+) -> Option<(u32, u32, u32, u32)> {
+    let (width, height) = img.dimensions();
+    let is_content = |x, y| !is_background(img.get_pixel(x, y), bg_color, tolerance);
+    let column_has_content = |x| (0..height).any(|y| is_content(x, y));
+    let row_has_content = |y| (0..width).any(|x| is_content(x, y));
+
+    let left = (0..width).find(|&x| column_has_content(x))?;
+    let right = (left..width).rev().find(|&x| column_has_content(x))?;
+    let top = (0..height).find(|&y| row_has_content(y))?;
+    let bottom = (top..height).rev().find(|&y| row_has_content(y))?;
+    Some((left, top, right, bottom))
+}
 
-    // If all pixels are background, return None (don't crop)
-    if left >= right || top >= bottom {
-        return None;
+/// The smallest `(x, y, width, height)` rectangle holding the content of every image
+///
+/// Each image is measured against its own background color. Animations
+/// crop all frames to this union so they keep a common size and nothing
+/// that appears in any frame is cut off. `None` when there is nothing to
+/// crop: all images are plain background, or the content already reaches
+/// every edge.
+pub fn content_bounds<'a, I>(images: impl IntoIterator<Item = &'a I>, tolerance: u8) -> Option<(u32, u32, u32, u32)>
+where
+    I: GenericImageView<Pixel = Rgba<u8>> + 'a,
+{
+    let mut dimensions = None;
+    let mut union: Option<(u32, u32, u32, u32)> = None;
+    for img in images {
+        dimensions.get_or_insert(img.dimensions());
+        let Some((left, top, right, bottom)) = find_crop_bounds(img, detect_background_color(img, tolerance), tolerance) else {
+            continue;
+        };
+        union = Some(match union {
+            Some((l, t, r, b)) => (l.min(left), t.min(top), r.max(right), b.max(bottom)),
+            None => (left, top, right, bottom),
+        });
     }
 
-    Some((left, top, right, bottom))
+    let (width, height) = dimensions?;
+    let (left, top, right, bottom) = union?;
+    let (crop_width, crop_height) = (right - left + 1, bottom - top + 1);
+    // Only crop if we're actually removing some border
+    (crop_width < width || crop_height < height).then_some((left, top, crop_width, crop_height))
 }
 
 /// Auto-crop an image by removing uniform borders
 ///
 /// Detects the background color from edge pixels and removes any
-/// continuous border of that color.
+/// continuous border of that color, or of full transparency.
 ///
 /// Returns the cropped image, or the original if no cropping is needed.
 pub fn auto_crop(img: DynamicImage) -> DynamicImage {
-    let bg_color = detect_background_color(&img);
-
-    if let Some((left, top, right, bottom)) = find_crop_bounds(&img, bg_color) {
-        let crop_width = right - left + 1;
-        let crop_height = bottom - top + 1;
+    auto_crop_with(img, DEFAULT_TOLERANCE)
+}
 
-        // Only crop if we're actually removing some border
-        let (orig_width, orig_height) = img.dimensions();
-        if crop_width < orig_width || crop_height < orig_height {
-            return img.crop_imm(left, top, crop_width, crop_height);
-        }
+/// [`auto_crop`] with pixels up to `tolerance` per channel away from the background counted as border
+pub fn auto_crop_with(img: DynamicImage, tolerance: u8) -> DynamicImage {
+    match content_bounds([&img], tolerance) {
+        Some((left, top, width, height)) => img.crop_imm(left, top, width, height),
+        None => img,
     }
-
-    // No cropping needed
-    img
 }
 
 /// Crop a fixed border of pixels from all sides
@@ -135,6 +169,20 @@ pub fn crop_border(img: DynamicImage, border: u32) -> DynamicImage {
     img.crop_imm(border, border, new_width, new_height)
 }
 
+/// Cut `region` out of an image
+///
+/// Regions reaching past the edges are clamped; a region that misses the
+/// image entirely leaves it uncropped.
+pub fn crop_region(img: DynamicImage, region: &CropRegion) -> DynamicImage {
+    let (width, height) = img.dimensions();
+    match region.resolve(width, height) {
+        Some((x, y, crop_width, crop_height)) if (crop_width, crop_height) != (width, height) => {
+            img.crop_imm(x, y, crop_width, crop_height)
+        }
+        _ => img,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -177,7 +225,7 @@ mod tests {
     #[test]
     fn test_detect_background_color() {
         let img = create_test_image_with_border(10, 10, 5);
-        let bg = detect_background_color(&img);
+        let bg = detect_background_color(&img, DEFAULT_TOLERANCE);
         assert_eq!(bg, Rgba([255, 255, 255, 255]));
     }
 
@@ -218,6 +266,76 @@ mod tests {
         assert_eq!(cropped.dimensions(), img.dimensions());
     }
 
+    #[test]
+    fn test_background_is_voted_by_edge_pixels() {
+        // A black logo in the top-left corner of a white page
+        let mut img = create_test_image_with_border(10, 10, 5);
+        for y in 0..3 {
+            for x in 0..3 {
+                img.as_mut_rgba8().expect("rgba").put_pixel(x, y, Rgba([0, 0, 0, 255]));
+            }
+        }
+        assert_eq!(detect_background_color(&img, DEFAULT_TOLERANCE), Rgba([255, 255, 255, 255]));
+        // The logo is content, so the crop reaches from it to the red square
+        assert_eq!(auto_crop(img).dimensions(), (15, 15));
+    }
+
+    #[test]
+    fn test_noisy_border_outvotes_content_on_an_edge() {
+        // A gray scan whose every pixel is jittered, with a black bar on the left edge
+        let mut seed = 1u32;
+        let mut img = ImageBuffer::from_fn(40, 40, |_, _| {
+            let mut jitter = || {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                200 + (seed >> 16) as u8 % 9 - 4
+            };
+            Rgba([jitter(), jitter(), jitter(), 255])
+        });
+        for y in 5..35 {
+            for x in 0..10 {
+                img.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+            }
+        }
+
+        let bg = detect_background_color(&img, DEFAULT_TOLERANCE);
+        assert!(bg.0[..3].iter().all(|&c| c.abs_diff(200) <= 4), "{bg:?}");
+        assert_eq!(content_bounds([&img], DEFAULT_TOLERANCE), Some((0, 5, 10, 30)));
+    }
+
+    #[test]
+    fn test_auto_crop_tolerance_and_transparency() {
+        let mut img = ImageBuffer::from_pixel(20, 20, Rgba([230, 230, 230, 255]));
+        for y in 5..15 {
+            for x in 5..15 {
+                img.put_pixel(x, y, Rgba([255, 255, 255, 255]));
+            }
+        }
+        let img = DynamicImage::ImageRgba8(img);
+        assert_eq!(auto_crop(img.clone()).dimensions(), (10, 10));
+        assert_eq!(auto_crop_with(img, 30).dimensions(), (20, 20));
+
+        // Transparent pixels with stray colour values still count as border
+        let sprite = ImageBuffer::from_fn(12, 12, |x, y| match (x, y) {
+            (4..8, 3..9) => Rgba([200, 0, 0, 255]),
+            _ => Rgba([x as u8 * 20, y as u8 * 20, 7, 0]),
+        });
+        assert_eq!(auto_crop(DynamicImage::ImageRgba8(sprite)).dimensions(), (4, 6));
+    }
+
+    #[test]
+    fn test_content_bounds_cover_every_frame() {
+        let frame = |x: u32| {
+            let mut img = ImageBuffer::from_pixel(20, 10, Rgba([0, 0, 0, 255]));
+            img.put_pixel(x, 5, Rgba([255, 255, 255, 255]));
+            img
+        };
+        let frames = [frame(3), frame(12)];
+        assert_eq!(content_bounds(&frames, DEFAULT_TOLERANCE), Some((3, 5, 10, 1)));
+
+        let blank = ImageBuffer::from_pixel(4, 4, Rgba([0, 0, 0, 255]));
+        assert_eq!(content_bounds([&blank], DEFAULT_TOLERANCE), None);
+    }
+
     #[test]
     fn test_auto_crop_asymmetric_border() {
         // Create image with asymmetric border
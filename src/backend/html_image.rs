@@ -0,0 +1,54 @@
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+
+use super::image_util::{blend_transparency, encode_png, scale_frame_for_graphics};
+use crate::backend::{Backend, RenderOptions, RenderedFrame};
+use crate::config::BackendKind;
+use crate::error::Result;
+use crate::image::Frame;
+
+/// Stands in for the graphics backends when exporting HTML
+///
+/// Each frame becomes one unchunked, never tmux-wrapped iTerm2 `File=`
+/// sequence, which [`crate::html::ansi_to_html`] turns into an `<img>`.
+#[derive(Debug)]
+pub struct HtmlImageBackend {
+    replaces: BackendKind,
+}
+
+impl HtmlImageBackend {
+    /// Render images for the HTML page in place of the `replaces` backend
+    pub fn new(replaces: BackendKind) -> Self {
+        Self { replaces }
+    }
+}
+
+impl Backend for HtmlImageBackend {
+    fn name(&self) -> &'static str {
+        "html-image"
+    }
+
+    fn supported_kind(&self) -> BackendKind {
+        self.replaces
+    }
+
+    fn render(&self, frame: &Frame, options: RenderOptions) -> Result<RenderedFrame> {
+        let (mut image, width_cells, height_cells) = scale_frame_for_graphics(frame, options);
+        blend_transparency(&mut image, options.background);
+        let png = encode_png(&image, "html")?;
+
+        let line = format!(
+            "\x1b]1337;File=inline=1;width={};height={}:{}\x07",
+            width_cells,
+            height_cells,
+            STANDARD.encode(png)
+        );
+
+        Ok(RenderedFrame {
+            lines: vec![line],
+            width_cells,
+            height_cells: 1,
+            delay: frame.delay,
+        })
+    }
+}
@@ -1,9 +1,10 @@
-use image::ImageEncoder;
 use image::codecs::png::PngEncoder;
-use image::imageops::{FilterType, resize};
+use image::imageops::{FilterType, resize, thumbnail, unsharpen};
+use image::{ImageBuffer, ImageEncoder, Rgba, RgbaImage};
 
 use super::BackgroundStyle;
 use crate::backend::RenderOptions;
+use crate::config::{RenderSizing, ResampleFilter, UnsharpMask};
 use crate::error::{Result, RimgError};
 use crate::image::Frame;
 
@@ -95,19 +96,94 @@ pub(crate) fn scale_frame(frame: &Frame, options: RenderOptions) -> (image::Rgba
     let width_cells = target_width.max(1).min(max_width_cells);
     let height_cells = target_height.max(1).min(max_height_cells);
 
-    let scaled = if target_width == pixels.width() && target_height == pixels.height() {
-        pixels.clone()
-    } else {
-        // Choose filter based on antialias setting
-        let filter = if options.sizing.antialias {
-            FilterType::Lanczos3 // High quality antialiasing
-        } else {
-            FilterType::Nearest // Fast, no antialiasing
+    let scaled = resample(pixels, target_width, target_height, &options.sizing, ResampleFilter::Lanczos3);
+
+    (sharpen(scaled, options.sharpen), width_cells, height_cells)
+}
+
+/// Run the unsharp mask on scaled pixels, so its radius is in displayed pixels
+pub(crate) fn sharpen(pixels: RgbaImage, mask: Option<UnsharpMask>) -> RgbaImage {
+    match mask {
+        Some(mask) => unsharpen(&pixels, mask.sigma, mask.threshold),
+        None => pixels,
+    }
+}
+
+/// Scale `pixels` to exactly `width` x `height`; every backend that resizes goes through here
+///
+/// An explicit `--filter` wins, `--no-antialias` falls back to nearest
+/// neighbour and otherwise the backend's `default` is used.
+pub(crate) fn resample(
+    pixels: &RgbaImage,
+    width: u32,
+    height: u32,
+    sizing: &RenderSizing,
+    default: ResampleFilter,
+) -> RgbaImage {
+    if (width, height) == pixels.dimensions() {
+        return pixels.clone();
+    }
+    let filter = match sizing.filter {
+        Some(filter) => filter,
+        None if !sizing.antialias => ResampleFilter::Nearest,
+        None => default,
+    };
+    let filter = filter_type(filter);
+    if !sizing.linear_light || filter == Some(FilterType::Nearest) {
+        return match filter {
+            Some(filter) => resize(pixels, width, height, filter),
+            None => thumbnail(pixels, width, height),
         };
-        resize(pixels, target_width, target_height, filter)
+    }
+
+    // Averaging gamma-encoded values darkens fine bright detail, so blend linear
+    // intensities; 16 bits keep the dark end of the curve distinct
+    let mut linear = ImageBuffer::<Rgba<u16>, _>::new(pixels.width(), pixels.height());
+    for (out, pixel) in linear.pixels_mut().zip(pixels.pixels()) {
+        let [r, g, b, a] = pixel.0;
+        out.0 = [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), u16::from(a) * 257];
+    }
+    let scaled = match filter {
+        Some(filter) => resize(&linear, width, height, filter),
+        None => thumbnail(&linear, width, height),
     };
+    let mut encoded = RgbaImage::new(width, height);
+    for (out, pixel) in encoded.pixels_mut().zip(scaled.pixels()) {
+        let [r, g, b, a] = pixel.0;
+        out.0 = [linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), (a / 257) as u8];
+    }
+    encoded
+}
 
-    (scaled, width_cells, height_cells)
+/// The `image` filter for `filter`, or `None` for the area average done by `thumbnail`
+fn filter_type(filter: ResampleFilter) -> Option<FilterType> {
+    match filter {
+        ResampleFilter::Nearest => Some(FilterType::Nearest),
+        ResampleFilter::Triangle => Some(FilterType::Triangle),
+        ResampleFilter::CatmullRom => Some(FilterType::CatmullRom),
+        ResampleFilter::Lanczos3 => Some(FilterType::Lanczos3),
+        ResampleFilter::Area => None,
+    }
+}
+
+fn srgb_to_linear(value: u8) -> u16 {
+    let value = f32::from(value) / 255.0;
+    let linear = if value <= 0.040_45 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    };
+    (linear * 65535.0).round() as u16
+}
+
+fn linear_to_srgb(value: u16) -> u8 {
+    let value = f32::from(value) / 65535.0;
+    let encoded = if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded * 255.0).round() as u8
 }
 
 pub(crate) fn encode_png(image: &image::RgbaImage, backend_name: &str) -> Result<Vec<u8>> {
@@ -179,3 +255,65 @@ pub(crate) fn checkerboard(x: u32, y: u32, pattern_size: u16) -> bool {
 fn pattern_rgb(color: crate::config::RgbColor) -> [u8; 3] {
     [color.r, color.g, color.b]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stripes() -> RgbaImage {
+        RgbaImage::from_fn(4, 4, |x, _| if x % 2 == 0 { image::Rgba([255; 4]) } else { image::Rgba([0, 0, 0, 255]) })
+    }
+
+    #[test]
+    fn resamples_with_the_chosen_filter_and_light() {
+        let mut sizing = RenderSizing {
+            filter: Some(ResampleFilter::Area),
+            ..RenderSizing::unconstrained()
+        };
+        let gamma = resample(&stripes(), 2, 2, &sizing, ResampleFilter::Triangle);
+        assert_eq!(gamma.get_pixel(0, 0).0, [128, 128, 128, 255]);
+
+        // Half white, half black is half the light, which sRGB encodes as 188
+        sizing.linear_light = true;
+        let linear = resample(&stripes(), 2, 2, &sizing, ResampleFilter::Triangle);
+        assert_eq!(linear.get_pixel(0, 0).0, [188, 188, 188, 255]);
+
+        // Without antialiasing and no explicit filter every pixel is a source pixel
+        let nearest = RenderSizing {
+            antialias: false,
+            ..RenderSizing::unconstrained()
+        };
+        let picked = resample(&stripes(), 2, 2, &nearest, ResampleFilter::Lanczos3);
+        assert!(picked.pixels().all(|pixel| pixel[0] == 0 || pixel[0] == 255));
+    }
+
+    #[test]
+    fn sharpens_the_scaled_pixels() {
+        let edge = RgbaImage::from_fn(16, 16, |x, _| image::Rgba(if x < 8 { [64, 64, 64, 255] } else { [192, 192, 192, 255] }));
+        let mask = UnsharpMask { sigma: 1.0, threshold: 0 };
+        let options = RenderOptions {
+            sizing: RenderSizing::unconstrained(),
+            terminal: crate::capabilities::TerminalSize {
+                columns: 4,
+                rows: 4,
+                width_pixels: None,
+                height_pixels: None,
+            },
+            background: BackgroundStyle {
+                color: None,
+                pattern: None,
+                pattern_size: 1,
+            },
+            pixelation: crate::config::PixelationMode::Quarter,
+            use_8bit_color: false,
+            compress_level: 1,
+            verbose: false,
+            sharpen: Some(mask),
+        };
+
+        let (scaled, _, _) = scale_frame(&Frame::single(edge.clone()), options);
+        let plain = resample(&edge, 4, 4, &options.sizing, ResampleFilter::Lanczos3);
+        assert_eq!(scaled, unsharpen(&plain, mask.sigma, mask.threshold));
+        assert_ne!(scaled, plain);
+    }
+}
@@ -152,6 +152,7 @@ mod tests {
                     use_8bit_color: false,
                     compress_level: 1,
                     verbose: false,
+                    sharpen: None,
                 },
             )
             .expect("render succeeds");
@@ -1,3 +1,6 @@
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
 use super::chunk_util::Base64Chunks;
 use super::image_util::{blend_transparency, encode_png, scale_frame};
 use crate::backend::{Backend, RenderOptions, RenderedFrame};
@@ -10,7 +13,11 @@ use crate::tmux;
 const BASE64_CHUNK: usize = 3072;
 
 #[derive(Debug, Default)]
-pub struct KittyBackend;
+pub struct KittyBackend {
+    // IDs of images this process transmitted, deleted again on interruption;
+    // a set, so an ID reused within the same millisecond is deleted once
+    transmitted: Mutex<BTreeSet<u32>>,
+}
 
 impl KittyBackend {
     fn build_chunks(
@@ -33,6 +40,9 @@ impl KittyBackend {
             .duration_since(UNIX_EPOCH)
             .map(|d| d.as_millis() as u32)
             .unwrap_or(1);
+        if let Ok(mut transmitted) = self.transmitted.lock() {
+            transmitted.insert(image_id);
+        }
 
         for (idx, chunk) in (&chunks).into_iter().enumerate() {
             let more = idx + 1 < total;
@@ -139,6 +149,24 @@ impl Backend for KittyBackend {
             delay: frame.delay,
         })
     }
+
+    fn cleanup(&self) -> Vec<String> {
+        let transmitted = match self.transmitted.lock() {
+            Ok(mut transmitted) => std::mem::take(&mut *transmitted),
+            Err(_) => return Vec::new(),
+        };
+        let in_tmux = tmux::in_multiplexer();
+
+        // d=I removes this image's placements and frees its data, leaving
+        // images from other programs in the window alone
+        transmitted
+            .into_iter()
+            .map(|id| {
+                let line = format!("\x1b_Ga=d,d=I,i={},q=2\x1b\\", id);
+                if in_tmux { tmux::wrap_for_tmux(&line) } else { line }
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -163,7 +191,7 @@ mod tests {
             delay: Duration::ZERO,
         };
 
-        let backend = KittyBackend;
+        let backend = KittyBackend::default();
         let rendered = backend
             .render(
                 &frame,
@@ -184,6 +212,7 @@ mod tests {
                     use_8bit_color: false,
                     compress_level: 1,
                     verbose: false,
+                    sharpen: None,
                 },
             )
             .expect("render succeeds");
@@ -194,4 +223,51 @@ mod tests {
         assert!(rendered.lines[0].starts_with("\x1b_G"));
         assert!(rendered.lines[0].ends_with("\x1b\\"));
     }
+
+    #[test]
+    fn cleanup_deletes_only_transmitted_images() {
+        let frame = ImageFrame {
+            pixels: ImageBuffer::from_pixel(2, 2, Rgba([0, 0, 255, 255])),
+            delay: Duration::ZERO,
+        };
+
+        let backend = KittyBackend::default();
+        assert!(backend.cleanup().is_empty());
+
+        for _ in 0..3 {
+            backend
+                .render(
+                    &frame,
+                    RenderOptions {
+                        sizing: RenderSizing::unconstrained(),
+                        terminal: TerminalSize {
+                            columns: 80,
+                            rows: 24,
+                            width_pixels: None,
+                            height_pixels: None,
+                        },
+                        background: BackgroundStyle {
+                            color: None,
+                            pattern: None,
+                            pattern_size: 1,
+                        },
+                        pixelation: crate::config::PixelationMode::Quarter,
+                        use_8bit_color: false,
+                        compress_level: 1,
+                        verbose: false,
+                        sharpen: None,
+                    },
+                )
+                .expect("render succeeds");
+        }
+
+        let cleanup = backend.cleanup();
+        // One scoped delete per distinct ID, never the window-wide d=A
+        assert!(!cleanup.is_empty() && cleanup.len() <= 3);
+        assert!(cleanup.iter().all(|line| line.contains("a=d,d=I,i=")), "{cleanup:?}");
+        assert!(!cleanup.iter().any(|line| line.contains("d=A")));
+        let distinct: BTreeSet<_> = cleanup.iter().collect();
+        assert_eq!(distinct.len(), cleanup.len());
+        assert!(backend.cleanup().is_empty());
+    }
 }
@@ -10,10 +10,12 @@ mod iterm2;
 mod sixel;
 
 mod chunk_util;
+mod html_image;
 mod image_util;
 
 use std::time::Duration;
 
+pub use html_image::HtmlImageBackend;
 pub use unicode::UnicodeBackend;
 
 #[cfg(feature = "kitty")]
@@ -25,7 +27,7 @@ pub use iterm2::ITerm2Backend;
 #[cfg(feature = "sixel")]
 pub use sixel::SixelBackend;
 
-use crate::config::{BackendKind, PixelationMode, RenderSizing, RgbColor};
+use crate::config::{BackendKind, PixelationMode, RenderSizing, RgbColor, UnsharpMask};
 use crate::error::Result;
 #[cfg(any(
     not(feature = "kitty"),
@@ -45,6 +47,8 @@ pub struct RenderOptions {
     pub use_8bit_color: bool,
     pub compress_level: u8,
     pub verbose: bool,
+    /// Unsharp mask that backends which scale images run on the scaled pixels
+    pub sharpen: Option<UnsharpMask>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -66,6 +70,23 @@ pub trait Backend {
     fn name(&self) -> &'static str;
     fn supported_kind(&self) -> BackendKind;
     fn render(&self, frame: &Frame, options: RenderOptions) -> Result<RenderedFrame>;
+
+    /// Whether the backend scales images to their cells itself
+    ///
+    /// Those backends apply [`RenderOptions::sharpen`] after scaling; the
+    /// rest leave scaling to the terminal and ignore it, so their images are
+    /// sharpened when decoded.
+    fn scales_images(&self) -> bool {
+        false
+    }
+
+    /// Escape sequences that remove anything the backend left on screen
+    ///
+    /// Written when playback is interrupted, so graphics protocols that keep
+    /// images in terminal memory can release them.
+    fn cleanup(&self) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 pub struct BackendFactory;
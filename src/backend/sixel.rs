@@ -21,6 +21,10 @@ impl Backend for SixelBackend {
         BackendKind::Sixel
     }
 
+    fn scales_images(&self) -> bool {
+        true
+    }
+
     fn render(&self, frame: &Frame, options: RenderOptions) -> Result<RenderedFrame> {
         let (mut image, width_cells, _height_cells) = scale_frame(frame, options);
         blend_transparency(&mut image, options.background);
@@ -194,6 +198,7 @@ mod tests {
                     pixelation: crate::config::PixelationMode::Quarter,
                     use_8bit_color: false,
                     compress_level: 1,
+                    sharpen: None,
                 },
             )
             .expect("render sixel");
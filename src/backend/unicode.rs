@@ -1,14 +1,24 @@
-use image::imageops::{FilterType, resize};
-use image::{Pixel, Rgba};
+use image::{Pixel, Rgba, RgbaImage};
 
+use super::image_util::{resample, sharpen};
 use crate::backend::{Backend, BackgroundStyle, RenderOptions, RenderedFrame};
-use crate::config::{BackendKind, PixelationMode};
+use crate::config::{BackendKind, PixelationMode, RenderSizing, ResampleFilter};
 use crate::error::Result;
 use crate::image::Frame;
 
 #[derive(Debug, Default)]
 pub struct UnicodeBackend;
 
+/// Block art has always been smoothed, so `--no-antialias` leaves it alone
+/// and only an explicit `--filter` changes the kernel
+fn scale_blocks(pixels: &RgbaImage, width: u32, height: u32, options: &RenderOptions) -> RgbaImage {
+    let sizing = RenderSizing {
+        antialias: true,
+        ..options.sizing
+    };
+    sharpen(resample(pixels, width, height, &sizing, ResampleFilter::Triangle), options.sharpen)
+}
+
 #[derive(Debug, Clone, Copy)]
 enum QuarterBlock {
     Empty,
@@ -53,6 +63,10 @@ impl Backend for UnicodeBackend {
         BackendKind::Unicode
     }
 
+    fn scales_images(&self) -> bool {
+        true
+    }
+
     fn render(&self, frame: &Frame, options: RenderOptions) -> Result<RenderedFrame> {
         match options.pixelation {
             PixelationMode::Half => self.render_half_blocks(frame, options),
@@ -117,17 +131,7 @@ impl UnicodeBackend {
             target_height += 1;
         }
 
-        let scaled =
-            if target_width == frame.pixels.width() && target_height == frame.pixels.height() {
-                frame.pixels.clone()
-            } else {
-                resize(
-                    &frame.pixels,
-                    target_width,
-                    target_height,
-                    FilterType::Triangle,
-                )
-            };
+        let scaled = scale_blocks(&frame.pixels, target_width, target_height, &options);
 
         let mut lines = Vec::with_capacity((target_height as usize + 1) / 2);
         let mut y = 0;
@@ -224,17 +228,7 @@ impl UnicodeBackend {
             target_height += 1;
         }
 
-        let scaled =
-            if target_width == frame.pixels.width() && target_height == frame.pixels.height() {
-                frame.pixels.clone()
-            } else {
-                resize(
-                    &frame.pixels,
-                    target_width,
-                    target_height,
-                    FilterType::Triangle,
-                )
-            };
+        let scaled = scale_blocks(&frame.pixels, target_width, target_height, &options);
 
         let mut lines = Vec::with_capacity((target_height as usize + 1) / 2);
         let mut y = 0;
@@ -63,6 +63,54 @@ pub fn ensure_tty_stdout() -> Result<()> {
     }
 }
 
+/// Terminal geometry pinned by `--term-size`/`--cell-size`, bypassing all probing
+///
+/// Returns `None` when neither is set, meaning the real terminal should be
+/// queried. With only a cell size the cell counts still come from the
+/// terminal, but the CSI 16t pixel query is never sent.
+pub fn fixed_terminal_size(term_size: Option<(u16, u16)>, cell_size: Option<(u16, u16)>) -> Option<TerminalSize> {
+    let (columns, rows) = match term_size {
+        Some(size) => size,
+        None if cell_size.is_some() => terminal_cells(),
+        None => return None,
+    };
+    let (width_pixels, height_pixels) = match cell_size {
+        Some((width, height)) => (Some(columns.saturating_mul(width)), Some(rows.saturating_mul(height))),
+        None => (None, None),
+    };
+
+    Some(TerminalSize {
+        columns,
+        rows,
+        width_pixels,
+        height_pixels,
+    })
+}
+
+/// Terminal size in cells
+///
+/// The terminal is asked first. `COLUMNS`/`LINES` only stand in when stdout
+/// isn't a terminal, such as output piped into a pager of another width, or
+/// when the query fails; a dimension they leave out still comes from the
+/// terminal, with 80x24 as the last resort.
+pub fn terminal_cells() -> (u16, u16) {
+    let queried = crossterm::terminal::size().ok();
+    let (columns, rows) = if queried.is_none() || !atty::is(Stream::Stdout) {
+        (env_cells("COLUMNS"), env_cells("LINES"))
+    } else {
+        (None, None)
+    };
+    let (current_columns, current_rows) = queried.unwrap_or((80, 24));
+    (columns.unwrap_or(current_columns), rows.unwrap_or(current_rows))
+}
+
+fn env_cells(name: &str) -> Option<u16> {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.trim().parse::<u16>().ok())
+        .filter(|value| *value > 0)
+}
+
 /// Try to query terminal pixel dimensions using XTWINOPS (CSI 16 t)
 /// Returns (width_pixels, height_pixels) if supported
 fn query_terminal_pixel_size() -> Option<(u16, u16)> {
@@ -134,7 +182,7 @@ fn query_terminal_pixel_size() -> Option<(u16, u16)> {
 }
 
 pub fn current_terminal_size() -> TerminalSize {
-    let (columns, rows) = crossterm::terminal::size().unwrap_or((80, 24));
+    let (columns, rows) = terminal_cells();
 
     // Try to detect pixel dimensions for accurate aspect ratio calculation
     let (width_pixels, height_pixels) = query_terminal_pixel_size()
@@ -0,0 +1,134 @@
+//! asciicast v2 recordings of rendered output
+//!
+//! [`CastWriter`] collects everything the renderer writes and turns each
+//! flushed chunk into an `[time, "o", data]` event. Time comes from a
+//! [`CastClock`] that the renderer advances by frame delays and waits
+//! instead of sleeping, so a recording takes as long as decoding does and
+//! needs no terminal.
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::Result;
+
+/// Virtual playback time shared between the renderer and a [`CastWriter`]
+#[derive(Debug, Clone, Default)]
+pub struct CastClock {
+    nanos: Arc<AtomicU64>,
+}
+
+impl CastClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move the clock forward by `delay`
+    pub fn advance(&self, delay: Duration) {
+        let nanos = u64::try_from(delay.as_nanos()).unwrap_or(u64::MAX);
+        self.nanos.fetch_add(nanos, Ordering::SeqCst);
+    }
+
+    /// Time since the recording started
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::SeqCst))
+    }
+}
+
+/// Writes an asciicast v2 stream to `inner`, one output event per flush
+pub struct CastWriter<W: Write> {
+    inner: W,
+    clock: CastClock,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> CastWriter<W> {
+    /// Start a recording of a `width`×`height` cell terminal by writing the header line
+    pub fn new(mut inner: W, width: u16, height: u16, clock: CastClock) -> Result<Self> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or(0);
+        writeln!(
+            inner,
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}}}",
+            width, height, timestamp
+        )?;
+        Ok(Self {
+            inner,
+            clock,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Emit whatever is still pending and flush the underlying writer
+    pub fn finish(mut self) -> Result<()> {
+        self.flush()?;
+        self.inner.flush()?;
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for CastWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    /// Turn the output written since the last flush into one event at the current clock time
+    fn flush(&mut self) -> io::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let mut event = format!("[{:.6}, \"o\", ", self.clock.elapsed().as_secs_f64());
+        push_json_string(&mut event, &String::from_utf8_lossy(&self.pending));
+        event.push_str("]\n");
+        self.pending.clear();
+        self.inner.write_all(event.as_bytes())
+    }
+}
+
+fn push_json_string(out: &mut String, text: &str) {
+    out.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if u32::from(ch) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", u32::from(ch));
+            }
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_flushed_chunks_with_clock_time() {
+        let clock = CastClock::new();
+        let mut out = Vec::new();
+        let mut cast = CastWriter::new(&mut out, 80, 24, clock.clone()).unwrap();
+        cast.write_all(b"\x1b[38;2;1;2;3m\"a\"\n").unwrap();
+        cast.flush().unwrap();
+        clock.advance(Duration::from_millis(250));
+        cast.flush().unwrap();
+        cast.write_all(b"\\").unwrap();
+        cast.finish().unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("{\"version\": 2, \"width\": 80, \"height\": 24, "));
+        assert_eq!(lines[1], "[0.000000, \"o\", \"\\u001b[38;2;1;2;3m\\\"a\\\"\\n\"]");
+        assert_eq!(lines[2], "[0.250000, \"o\", \"\\\\\"]");
+    }
+}
@@ -6,10 +6,15 @@ use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::OnceLock;
 
-use clap::{ArgAction, Parser};
+use clap::{ArgAction, ArgMatches, CommandFactory, FromArgMatches, Parser};
+use clap::parser::ValueSource;
 
-use crate::config::{BackendKind, BackgroundColor, Config, GridOptions, PixelationMode, RenderSizing, RotationMode, RgbColor};
+use crate::capabilities::{current_terminal_size, fixed_terminal_size};
+use crate::config::{BackendKind, BackgroundColor, Config, CropRegion, Flip, GridOptions, OutputFormat, PageSelection, PixelationMode, RenderSizing, ResampleFilter, RotationMode, RgbColor, SortOrder, ToneMapOperator, UnsharpMask};
 use crate::error::{Result, RimgError};
+use crate::image::DecodeLimits;
+use crate::inputs::{DirectoryOptions, expand_inputs};
+use crate::preferences::{self, ConfigFile, Preferences};
 
 #[derive(Debug, Parser)]
 #[command(author, version, about = "Terminal image viewer rewritten in Rust", long_about = None)]
@@ -26,33 +31,68 @@ pub struct Cli {
     #[arg(short = 'F', long = "filelist-relative", value_name = "FILE")]
     filelist_relative: Vec<PathBuf>,
 
-    /// Renderer backend to use
+    /// Descend into subdirectories of directory inputs
+    #[arg(short = 'r', long = "recursive", action = ArgAction::SetTrue)]
+    recursive: bool,
+
+    /// Only take files with these extensions from directories (e.g. `png,jpg`)
+    #[arg(long = "ext", value_name = "EXT", value_delimiter = ',')]
+    extensions: Vec<String>,
+
+    /// Order of files found in directories (name, mtime, size, exif, random)
+    #[arg(
+        long = "sort",
+        value_name = "ORDER",
+        default_value = "name",
+        value_parser = parse_sort,
+    )]
+    sort: SortOrder,
+
+    /// Seed for `--sort random` to get a reproducible order
+    #[arg(long = "seed", value_name = "N")]
+    seed: Option<u64>,
+
+    /// Skip the config file ($XDG_CONFIG_HOME/showme/config.toml)
+    #[arg(long = "no-config", action = ArgAction::SetTrue)]
+    no_config: bool,
+
+    /// Renderer backend to use [default: auto]
     #[arg(
         long = "backend",
         value_name = "BACKEND",
-        default_value = "auto",
         value_parser = parse_backend,
     )]
-    backend: BackendKind,
+    backend: Option<BackendKind>,
 
-    /// Pixelation mode for Unicode backend (half or quarter blocks)
+    /// Pixelation mode for Unicode backend (half or quarter blocks) [default: quarter]
     #[arg(
         short = 'p',
         long = "pixelation",
         value_name = "MODE",
-        default_value = "quarter",
         value_parser = parse_pixelation,
     )]
-    pixelation: PixelationMode,
+    pixelation: Option<PixelationMode>,
 
-    /// Image rotation mode (exif or off)
+    /// Image rotation: exif, off, or 90/180/270 degrees clockwise on top of EXIF [default: exif]
     #[arg(
         long = "rotate",
         value_name = "MODE",
-        default_value = "exif",
         value_parser = parse_rotation,
+        allow_hyphen_values = true,
     )]
-    rotation: RotationMode,
+    rotation: Option<RotationMode>,
+
+    /// Mirror images horizontally (h), vertically (v) or both (hv)
+    #[arg(long = "flip", value_name = "h|v|hv", value_parser = parse_flip)]
+    flip: Option<Flip>,
+
+    /// Show only this region, in pixels or percent (e.g., `--crop 50%x50%+25%+25%`)
+    #[arg(long = "crop", value_name = "WxH+X+Y", value_parser = parse_crop)]
+    crop: Option<CropRegion>,
+
+    /// Show only these PDF pages, 1-based (e.g., `--pages 1,3,10-12` or `--pages 5-`)
+    #[arg(long = "pages", value_name = "LIST", value_parser = parse_pages)]
+    pages: Option<PageSelection>,
 
     /// Output geometry in character cells (WIDTHxHEIGHT, WIDTHx, or xHEIGHT)
     #[arg(short = 'g', long = "geometry", value_name = "SPEC")]
@@ -86,6 +126,14 @@ pub struct Cli {
     #[arg(short = 'a', long = "no-antialias", action = ArgAction::SetTrue)]
     no_antialias: bool,
 
+    /// Resampling filter: nearest, triangle, catmull-rom, lanczos3 or area [default: per backend]
+    #[arg(long = "filter", value_name = "FILTER", value_parser = parse_filter)]
+    filter: Option<ResampleFilter>,
+
+    /// Scale in linear light so bright and dark detail average correctly
+    #[arg(long = "linear-light", action = ArgAction::SetTrue)]
+    linear_light: bool,
+
     /// Arrange images in a grid of COLS or COLSxROWS
     #[arg(long = "grid", value_name = "COLS[xROWS]")]
     grid: Option<String>,
@@ -115,9 +163,13 @@ pub struct Cli {
     max_duration: Option<String>,
 
     /// Reduce informational output
-    #[arg(long = "quiet", short = 'q', action = ArgAction::SetTrue)]
+    #[arg(long = "quiet", short = 'q', action = ArgAction::SetTrue, overrides_with = "no_quiet")]
     quiet: bool,
 
+    /// Undo a stored `quiet = true`
+    #[arg(long = "no-quiet", action = ArgAction::SetTrue)]
+    no_quiet: bool,
+
     /// Print verbose terminal and performance information
     #[arg(long = "verbose", short = 'v', action = ArgAction::SetTrue, conflicts_with = "quiet")]
     verbose: bool,
@@ -139,57 +191,150 @@ pub struct Cli {
     title: Option<String>,
 
     /// Center image horizontally within terminal width (non-grid mode).
-    #[arg(long = "center", action = ArgAction::SetTrue)]
+    #[arg(long = "center", action = ArgAction::SetTrue, overrides_with = "no_center")]
     center: bool,
 
+    /// Don't center, overriding a stored `center = true`.
+    #[arg(long = "no-center", action = ArgAction::SetTrue)]
+    no_center: bool,
+
     /// Render inside the terminal's alternate screen buffer and restore on exit.
-    #[arg(long = "alternate-screen", action = ArgAction::SetTrue)]
+    #[arg(long = "alternate-screen", action = ArgAction::SetTrue, overrides_with = "no_alternate_screen")]
     alternate_screen: bool,
 
+    /// Draw on the normal screen, overriding a stored `alternate-screen = true`.
+    #[arg(long = "no-alternate-screen", action = ArgAction::SetTrue)]
+    no_alternate_screen: bool,
+
     /// Don't hide cursor while displaying images
     #[arg(short = 'E', long = "show-cursor", action = ArgAction::SetTrue)]
     show_cursor: bool,
 
-    /// Background color to blend transparent pixels (`auto`, `none`, `#rrggbb`, `rgb()`).
-    #[arg(
-        short = 'b',
-        long = "background",
-        value_name = "COLOR",
-        default_value = "auto"
-    )]
-    background: String,
+    /// Background color to blend transparent pixels (`auto`, `none`, `#rrggbb`, `rgb()`) [default: auto]
+    #[arg(short = 'b', long = "background", value_name = "COLOR")]
+    background: Option<String>,
 
     /// Checkerboard pattern color for transparency.
     #[arg(short = 'B', long = "pattern", value_name = "COLOR")]
     pattern: Option<String>,
 
-    /// Scale factor for transparency checkerboard pattern.
-    #[arg(long = "pattern-size", value_name = "INT", default_value_t = 1)]
-    pattern_size: u16,
+    /// Scale factor for transparency checkerboard pattern [default: 1]
+    #[arg(long = "pattern-size", value_name = "INT")]
+    pattern_size: Option<u16>,
 
     /// Auto-crop image by removing same-color borders.
     #[arg(long = "auto-crop", action = ArgAction::SetTrue)]
     auto_crop: bool,
 
+    /// Colour difference per channel (0-255) still trimmed as border by --auto-crop
+    #[arg(long = "auto-crop-tolerance", value_name = "0-255", default_value_t = 10)]
+    auto_crop_tolerance: u8,
+
+    /// Tone-mapping operator for HDR/EXR images: aces, reinhard, hable or clip [default: aces]
+    #[arg(
+        long = "tone-map",
+        value_name = "OPERATOR",
+        default_value = "aces",
+        value_parser = parse_tone_map,
+    )]
+    tone_map: ToneMapOperator,
+
+    /// Exposure adjustment for HDR/EXR images in stops (e.g., `--exposure -1.5`)
+    #[arg(long = "exposure", value_name = "STOPS", default_value_t = 0.0, allow_hyphen_values = true)]
+    exposure: f32,
+
+    /// Brightness change in percent, -100 to 100 (e.g., `--brightness 20`)
+    #[arg(long = "brightness", value_name = "PERCENT", default_value_t = 0.0, allow_hyphen_values = true)]
+    brightness: f32,
+
+    /// Contrast change in percent; negative values reduce contrast
+    #[arg(long = "contrast", value_name = "PERCENT", default_value_t = 0.0, allow_hyphen_values = true)]
+    contrast: f32,
+
+    /// Gamma correction; values above 1 lift shadows [default: 1.0]
+    #[arg(long = "gamma", value_name = "GAMMA", default_value_t = 1.0, value_parser = parse_gamma)]
+    gamma: f32,
+
+    /// Saturation factor: 0 is gray, 1 unchanged, 2 doubles it [default: 1.0]
+    #[arg(long = "saturation", value_name = "FACTOR", default_value_t = 1.0)]
+    saturation: f32,
+
+    /// Show images in grayscale
+    #[arg(long = "grayscale", action = ArgAction::SetTrue)]
+    grayscale: bool,
+
+    /// Invert colours (negatives, dark-mode screenshots)
+    #[arg(long = "invert", action = ArgAction::SetTrue)]
+    invert: bool,
+
+    /// Unsharp mask with blur radius SIGMA and optional threshold 0-255 (e.g., `--sharpen 1.5,4`)
+    #[arg(long = "sharpen", value_name = "SIGMA[,THRESHOLD]", value_parser = parse_sharpen)]
+    sharpen: Option<UnsharpMask>,
+
+    /// Ignore embedded ICC colour profiles instead of converting to sRGB
+    #[arg(long = "no-icc", action = ArgAction::SetTrue)]
+    no_icc: bool,
+
     /// Crop fixed border of pixels before auto-crop (e.g., `--crop-border 10`).
     #[arg(long = "crop-border", value_name = "PIXELS", default_value_t = 0)]
     crop_border: u32,
 
+    /// Lay out for a terminal of COLSxROWS cells instead of the real one (overrides COLUMNS/LINES)
+    #[arg(long = "term-size", value_name = "COLSxROWS", value_parser = parse_term_size)]
+    term_size: Option<(u16, u16)>,
+
+    /// Assume character cells of WxH pixels instead of querying the terminal
+    #[arg(long = "cell-size", value_name = "WxH", value_parser = parse_cell_size)]
+    cell_size: Option<(u16, u16)>,
+
     /// Write output to file instead of stdout
     #[arg(short = 'o', long = "output", value_name = "FILE")]
     output_file: Option<PathBuf>,
 
+    /// Output encoding: ansi (terminal escapes), html (standalone page) or png (unicode art at --cell-size)
+    #[arg(
+        long = "output-format",
+        value_name = "FORMAT",
+        default_value = "ansi",
+        value_parser = parse_output_format,
+    )]
+    output_format: OutputFormat,
+
+    /// Record playback to an asciicast v2 file instead of drawing it (no terminal needed)
+    #[arg(long = "record", value_name = "FILE", conflicts_with = "output_file")]
+    record: Option<PathBuf>,
+
     /// Use 8-bit color mode (256 colors) for Unicode renderer
-    #[arg(long = "color8", action = ArgAction::SetTrue)]
+    #[arg(long = "color8", action = ArgAction::SetTrue, overrides_with = "no_color8")]
     color8: bool,
 
+    /// Use 24-bit color, overriding a stored `color8 = true`
+    #[arg(long = "no-color8", action = ArgAction::SetTrue)]
+    no_color8: bool,
+
+    /// Refuse images, frames or PDF pages larger than N pixels (width × height)
+    #[arg(long = "max-pixels", value_name = "N")]
+    max_pixels: Option<u64>,
+
+    /// Refuse inputs larger than SIZE bytes (suffixes K, M, G; e.g., "50M")
+    #[arg(long = "max-bytes", value_name = "SIZE", value_parser = parse_byte_size)]
+    max_bytes: Option<u64>,
+
+    /// Refuse animations, videos and PDFs with more than N frames or pages
+    #[arg(long = "max-decode-frames", value_name = "N")]
+    max_decode_frames: Option<usize>,
+
+    /// Give up on an input after decoding it for this long (e.g., "5s", "500ms")
+    #[arg(long = "max-decode-time", value_name = "DURATION")]
+    max_decode_time: Option<String>,
+
     /// Number of threads for parallel image decoding
     #[arg(long = "threads", value_name = "N")]
     threads: Option<usize>,
 
     /// Compression level for graphics protocols (0-9, default: 1)
-    #[arg(long = "compress", value_name = "LEVEL", default_value_t = 1)]
-    compress: u8,
+    #[arg(long = "compress", value_name = "LEVEL")]
+    compress: Option<u8>,
 
     /// Force video decoding (skip image probe)
     #[arg(long = "force-video", action = ArgAction::SetTrue, conflicts_with = "force_image")]
@@ -210,6 +355,14 @@ pub struct Cli {
 
 impl Cli {
     pub fn into_config(self) -> Result<Config> {
+        let stored = stored_preferences(self.no_config)?;
+        self.into_config_with(stored)
+    }
+
+    /// Build the config, using `stored` for any preference not given on the command line
+    pub fn into_config_with(self, stored: Preferences) -> Result<Config> {
+        let prefs = self.preferences().or(stored);
+
         // Collect all inputs from command line and file lists
         let mut all_inputs = self.inputs.clone();
 
@@ -225,6 +378,20 @@ impl Cli {
             all_inputs.extend(images);
         }
 
+        // Replace directories with the media files inside them
+        let directory_options = DirectoryOptions {
+            recursive: self.recursive,
+            extensions: self
+                .extensions
+                .iter()
+                .map(|ext| ext.trim().trim_start_matches('.').to_ascii_lowercase())
+                .filter(|ext| !ext.is_empty())
+                .collect(),
+            sort: self.sort,
+            seed: self.seed,
+        };
+        let all_inputs = expand_inputs(all_inputs, &directory_options)?;
+
         if all_inputs.is_empty() {
             return Err(RimgError::MissingInput);
         }
@@ -239,17 +406,24 @@ impl Cli {
 
         let (clear_once, clear_between) = parse_clear(self.clear.as_deref())?;
 
-        if self.pattern_size == 0 {
+        let pattern_size = prefs.pattern_size.unwrap_or(1);
+        if pattern_size == 0 {
             return Err(RimgError::other("pattern-size must be greater than zero"));
         }
 
-        let background = parse_background_color(&self.background)?;
-        let pattern_color = match self.pattern.as_deref() {
+        let background = parse_background_color(prefs.background.as_deref().unwrap_or("auto"))?;
+        let pattern_color = match prefs.pattern.as_deref() {
             Some(raw) => parse_optional_color(raw)?,
             None => None,
         };
 
         let max_duration = parse_optional_duration(self.max_duration.as_deref())?;
+        let decode_limits = DecodeLimits {
+            max_pixels: self.max_pixels,
+            max_bytes: self.max_bytes,
+            max_frames: self.max_decode_frames,
+            max_decode_time: parse_optional_duration(self.max_decode_time.as_deref())?,
+        };
 
         // Parse geometry or use individual width/height
         let (width_cells, height_cells) = if let Some(ref geom) = self.geometry {
@@ -266,28 +440,33 @@ impl Cli {
             parse_scroll_settings(self.scroll.as_deref(), self.delta_move.as_deref())?;
 
         // Validate compress level
-        if self.compress > 9 {
+        let compress_level = prefs.compress.unwrap_or(1);
+        if compress_level > 9 {
             return Err(RimgError::other("compress level must be between 0 and 9"));
         }
 
         // Validate width stretch if provided
-        if let Some(stretch) = self.width_stretch {
+        if let Some(stretch) = prefs.width_stretch {
             if stretch <= 0.0 {
                 return Err(RimgError::other("width-stretch must be positive"));
             }
         }
 
+        // Pinned geometry must not depend on the terminal the command happens to run in
+        let fixed_terminal = fixed_terminal_size(self.term_size, self.cell_size);
+
         // Use provided width_stretch or auto-detect from terminal
-        let width_stretch = self.width_stretch.unwrap_or_else(|| {
-            use crate::capabilities::current_terminal_size;
-            current_terminal_size().recommended_width_stretch()
+        let width_stretch = prefs.width_stretch.unwrap_or_else(|| {
+            fixed_terminal
+                .unwrap_or_else(current_terminal_size)
+                .recommended_width_stretch()
         });
 
         Ok(Config {
             inputs: all_inputs,
-            backend: self.backend,
-            pixelation: self.pixelation,
-            rotation: self.rotation,
+            backend: prefs.backend.unwrap_or(BackendKind::Auto),
+            pixelation: prefs.pixelation.unwrap_or_default(),
+            rotation: prefs.rotate.unwrap_or_default(),
             sizing: RenderSizing {
                 width_cells,
                 height_cells,
@@ -297,6 +476,8 @@ impl Cli {
                 upscale_integer,
                 width_stretch,
                 antialias: !self.no_antialias,
+                filter: self.filter,
+                linear_light: self.linear_light,
             },
             grid,
             loop_forever: self.loop_forever,
@@ -304,33 +485,173 @@ impl Cli {
             max_frames: self.max_frames,
             frame_offset: self.frame_offset,
             max_duration,
-            quiet: self.quiet,
+            // An explicit --verbose beats a stored `quiet = true`
+            quiet: prefs.quiet.unwrap_or(false) && !self.verbose,
             verbose: self.verbose,
             clear_between,
             clear_once,
             wait_between_images,
             wait_between_rows,
-            title_format: self.title,
-            center: self.center,
-            alternate_screen: self.alternate_screen,
+            title_format: prefs.title,
+            center: prefs.center.unwrap_or(false),
+            alternate_screen: prefs.alternate_screen.unwrap_or(false),
             hide_cursor: !self.show_cursor,
             background,
             pattern_color,
-            pattern_size: self.pattern_size,
+            pattern_size,
             auto_crop: self.auto_crop,
+            auto_crop_tolerance: self.auto_crop_tolerance,
             crop_border: self.crop_border,
             output_file: self.output_file,
-            use_8bit_color: self.color8,
-            threads: self.threads,
-            compress_level: self.compress,
+            output_format: self.output_format,
+            use_8bit_color: prefs.color8.unwrap_or(false),
+            threads: prefs.threads,
+            compress_level,
             force_video: self.force_video,
             force_image: self.force_image,
             scroll_animation,
             scroll_delay,
             scroll_dx,
             scroll_dy,
+            term_size: self.term_size,
+            cell_size: self.cell_size,
+            record: self.record,
+            decode_limits,
+            tone_map: self.tone_map,
+            exposure: self.exposure,
+            color_management: !self.no_icc,
+            brightness: self.brightness,
+            contrast: self.contrast,
+            gamma: self.gamma,
+            saturation: self.saturation,
+            grayscale: self.grayscale,
+            invert: self.invert,
+            sharpen: self.sharpen,
+            flip: self.flip,
+            crop: self.crop,
+            pages: self.pages.clone(),
         })
     }
+
+    /// The preferences given explicitly on this command line
+    fn preferences(&self) -> Preferences {
+        let flag = |on: bool, off: bool| match (on, off) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        };
+        Preferences {
+            backend: self.backend,
+            pixelation: self.pixelation,
+            rotate: self.rotation,
+            background: self.background.clone(),
+            pattern: self.pattern.clone(),
+            pattern_size: self.pattern_size,
+            width_stretch: self.width_stretch,
+            compress: self.compress,
+            threads: self.threads,
+            title: self.title.clone(),
+            center: flag(self.center, self.no_center),
+            alternate_screen: flag(self.alternate_screen, self.no_alternate_screen),
+            color8: flag(self.color8, self.no_color8),
+            quiet: flag(self.quiet, self.no_quiet),
+        }
+    }
+}
+
+/// Defaults from `SHOWME_OPTS` layered over the config file (unless `skip_file`)
+fn stored_preferences(skip_file: bool) -> Result<Preferences> {
+    let prefs = env_preferences()?;
+    if skip_file {
+        return Ok(prefs);
+    }
+
+    let file = match preferences::default_config_path() {
+        Some(path) => ConfigFile::load(&path)?,
+        None => None,
+    };
+    match file {
+        Some(file) => {
+            let terminal = crate::capabilities::detect_terminal_name();
+            Ok(prefs.or(file.preferences_for(terminal.as_deref())))
+        }
+        None => Ok(prefs),
+    }
+}
+
+/// Parse `SHOWME_OPTS` with the regular command-line parser
+fn env_preferences() -> Result<Preferences> {
+    match std::env::var(preferences::OPTIONS_ENV) {
+        Ok(raw) if !raw.trim().is_empty() => options_preferences(&raw),
+        _ => Ok(Preferences::default()),
+    }
+}
+
+/// Argument IDs of the flags that map onto [`Preferences`]
+const PREFERENCE_ARGS: &[&str] = &[
+    "backend",
+    "pixelation",
+    "rotation",
+    "background",
+    "pattern",
+    "pattern_size",
+    "width_stretch",
+    "compress",
+    "threads",
+    "title",
+    "center",
+    "no_center",
+    "alternate_screen",
+    "no_alternate_screen",
+    "color8",
+    "no_color8",
+    "quiet",
+    "no_quiet",
+];
+
+/// Parse `SHOWME_OPTS`, refusing flags that aren't preferences rather than dropping them
+fn options_preferences(raw: &str) -> Result<Preferences> {
+    let args = shlex::split(raw).ok_or_else(|| {
+        RimgError::other(format!("{} has unbalanced quotes", preferences::OPTIONS_ENV))
+    })?;
+    let matches = Cli::command()
+        .try_get_matches_from(std::iter::once("showme".to_string()).chain(args))
+        .map_err(|err| RimgError::other(format!("invalid {}: {}", preferences::OPTIONS_ENV, err)))?;
+    let cli = Cli::from_arg_matches(&matches)
+        .map_err(|err| RimgError::other(format!("invalid {}: {}", preferences::OPTIONS_ENV, err)))?;
+
+    if !cli.inputs.is_empty() {
+        return Err(RimgError::other(format!(
+            "{} may only contain options, not input paths",
+            preferences::OPTIONS_ENV
+        )));
+    }
+
+    if let Some(flag) = unsupported_option(&matches) {
+        return Err(RimgError::other(format!(
+            "{} doesn't support {}; only backend, pixelation, rotate, background, pattern, \
+             pattern-size, width-stretch, compress, threads, title, center, alternate-screen, \
+             color8 and quiet can be set there",
+            preferences::OPTIONS_ENV,
+            flag
+        )));
+    }
+
+    Ok(cli.preferences())
+}
+
+/// The first flag given in `matches` that isn't a preference, as it would be typed
+fn unsupported_option(matches: &ArgMatches) -> Option<String> {
+    let command = Cli::command();
+    let arg = command.get_arguments().find(|arg| {
+        let id = arg.get_id().as_str();
+        !PREFERENCE_ARGS.contains(&id) && matches.value_source(id) == Some(ValueSource::CommandLine)
+    })?;
+    Some(match (arg.get_long(), arg.get_short()) {
+        (Some(long), _) => format!("--{long}"),
+        (None, Some(short)) => format!("-{short}"),
+        (None, None) => arg.get_id().to_string(),
+    })
 }
 
 fn parse_backend(value: &str) -> std::result::Result<BackendKind, String> {
@@ -345,6 +666,45 @@ fn parse_rotation(value: &str) -> std::result::Result<RotationMode, String> {
     RotationMode::from_str(value)
 }
 
+fn parse_flip(value: &str) -> std::result::Result<Flip, String> {
+    value.parse()
+}
+
+fn parse_crop(value: &str) -> std::result::Result<CropRegion, String> {
+    value.parse()
+}
+
+fn parse_pages(value: &str) -> std::result::Result<PageSelection, String> {
+    value.parse()
+}
+
+fn parse_tone_map(value: &str) -> std::result::Result<ToneMapOperator, String> {
+    value.parse()
+}
+
+fn parse_filter(value: &str) -> std::result::Result<ResampleFilter, String> {
+    value.parse()
+}
+
+fn parse_gamma(value: &str) -> std::result::Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(gamma) if gamma.is_finite() && gamma > 0.0 => Ok(gamma),
+        _ => Err(format!("invalid gamma '{}'; expected a positive number", value)),
+    }
+}
+
+fn parse_sharpen(value: &str) -> std::result::Result<UnsharpMask, String> {
+    value.parse()
+}
+
+fn parse_sort(value: &str) -> std::result::Result<SortOrder, String> {
+    SortOrder::from_str(value)
+}
+
+fn parse_output_format(value: &str) -> std::result::Result<OutputFormat, String> {
+    OutputFormat::from_str(value)
+}
+
 fn parse_geometry(spec: &str) -> Result<(Option<u32>, Option<u32>)> {
 
     // Parse WIDTHxHEIGHT, WIDTHx, or xHEIGHT
@@ -446,6 +806,24 @@ fn parse_grid(spec: &str, spacing: u16) -> Result<GridOptions> {
     })
 }
 
+fn parse_term_size(value: &str) -> std::result::Result<(u16, u16), String> {
+    parse_dimensions(value, "term-size", "COLSxROWS")
+}
+
+fn parse_cell_size(value: &str) -> std::result::Result<(u16, u16), String> {
+    parse_dimensions(value, "cell-size", "WxH")
+}
+
+fn parse_dimensions(value: &str, flag: &str, shape: &str) -> std::result::Result<(u16, u16), String> {
+    let parsed = value
+        .split_once(['x', 'X'])
+        .and_then(|(a, b)| Some((a.trim().parse::<u16>().ok()?, b.trim().parse::<u16>().ok()?)));
+    match parsed {
+        Some((a, b)) if a > 0 && b > 0 => Ok((a, b)),
+        _ => Err(format!("{flag} must be {shape} with positive integers, got '{value}'")),
+    }
+}
+
 fn parse_optional_duration(input: Option<&str>) -> Result<Option<std::time::Duration>> {
     match input {
         Some(raw) => Ok(Some(parse_duration(raw)?)),
@@ -453,6 +831,22 @@ fn parse_optional_duration(input: Option<&str>) -> Result<Option<std::time::Dura
     }
 }
 
+fn parse_byte_size(value: &str) -> std::result::Result<u64, String> {
+    let trimmed = value.trim();
+    let (digits, multiplier) = match trimmed.char_indices().last() {
+        Some((at, 'k' | 'K')) => (&trimmed[..at], 1 << 10),
+        Some((at, 'm' | 'M')) => (&trimmed[..at], 1 << 20),
+        Some((at, 'g' | 'G')) => (&trimmed[..at], 1 << 30),
+        _ => (trimmed, 1),
+    };
+    digits
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .and_then(|count| count.checked_mul(multiplier))
+        .ok_or_else(|| format!("--max-bytes must be a byte count like 500K or 50M, got '{value}'"))
+}
+
 fn parse_clear(spec: Option<&str>) -> Result<(bool, bool)> {
     match spec {
         None => Ok((false, false)),
@@ -661,6 +1055,7 @@ fn read_filelist(filelist_path: &Path, relative_to_filelist: bool) -> Result<Vec
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::CropLength;
 
     #[test]
     fn parses_grid_with_rows() {
@@ -719,6 +1114,32 @@ mod tests {
         assert_eq!(mode, RotationMode::Off);
     }
 
+    #[test]
+    fn parses_manual_rotation_flip_and_crop() {
+        assert_eq!(parse_rotation("270"), Ok(RotationMode::Clockwise270));
+        assert!(parse_rotation("45").is_err());
+        assert_eq!(parse_flip("hv"), Ok(Flip::Both));
+
+        let region = parse_crop("50%x200+10+5%").expect("crop");
+        assert_eq!(region.width, CropLength::Percent(50.0));
+        assert_eq!(region.resolve(100, 100), Some((10, 5, 50, 95)));
+        assert_eq!(parse_crop("20x10").expect("crop").resolve(100, 100), Some((0, 0, 20, 10)));
+        assert!(parse_crop("0x10").is_err());
+        assert!(parse_crop("10x10+5").is_err());
+        assert!(parse_crop("150%x10").is_err());
+    }
+
+    #[test]
+    fn parses_page_selections() {
+        let pages = parse_pages("3,1-2,2,9-").expect("pages");
+        assert_eq!(pages.indices(10), vec![2, 0, 1, 8, 9]);
+        assert_eq!(pages.indices(2), vec![0, 1]);
+        assert!(parse_pages("5").expect("pages").indices(4).is_empty());
+        assert!(parse_pages("0").is_err());
+        assert!(parse_pages("4-2").is_err());
+        assert!(parse_pages("1,,2").is_err());
+    }
+
     #[test]
     fn reads_filelist_with_comments() {
         use std::io::Write;
@@ -789,7 +1210,89 @@ mod tests {
     #[test]
     fn parses_alternate_screen_flag() {
         let cli = Cli::parse_from(["showme", "--alternate-screen", "img.png"]);
-        let config = cli.into_config().expect("config");
+        let config = cli.into_config_with(Preferences::default()).expect("config");
         assert!(config.alternate_screen);
     }
+
+    #[test]
+    fn negative_flags_switch_stored_preferences_off() {
+        let stored = Preferences {
+            center: Some(true),
+            alternate_screen: Some(true),
+            color8: Some(true),
+            quiet: Some(true),
+            ..Preferences::default()
+        };
+
+        let cli = Cli::parse_from([
+            "showme",
+            "--no-center",
+            "--no-alternate-screen",
+            "--no-color8",
+            "--no-quiet",
+            "img.png",
+        ]);
+        let config = cli.into_config_with(stored).expect("config");
+        assert!(!config.center && !config.alternate_screen && !config.use_8bit_color && !config.quiet);
+
+        // The last of a pair wins
+        let cli = Cli::parse_from(["showme", "--no-center", "--center", "img.png"]);
+        assert!(cli.into_config_with(Preferences::default()).expect("config").center);
+    }
+
+    #[test]
+    fn showme_opts_refuses_flags_that_are_not_preferences() {
+        let prefs = options_preferences("--backend unicode --no-center").expect("preferences");
+        assert_eq!(prefs.backend, Some(BackendKind::Unicode));
+        assert_eq!(prefs.center, Some(false));
+
+        let err = options_preferences("--center --auto-crop").expect_err("unsupported");
+        assert!(err.to_string().contains("--auto-crop"), "{err}");
+        let err = options_preferences("--filter lanczos").expect_err("unsupported");
+        assert!(err.to_string().contains("--filter"), "{err}");
+    }
+
+    #[test]
+    fn stored_preferences_fill_unset_flags() {
+        let stored = Preferences {
+            backend: Some(BackendKind::Unicode),
+            pixelation: Some(PixelationMode::Half),
+            center: Some(true),
+            ..Preferences::default()
+        };
+
+        let cli = Cli::parse_from(["showme", "img.png"]);
+        let config = cli.into_config_with(stored).expect("config");
+        assert_eq!(config.backend, BackendKind::Unicode);
+        assert_eq!(config.pixelation, PixelationMode::Half);
+        assert!(config.center);
+    }
+
+    #[test]
+    fn explicit_flags_beat_stored_preferences() {
+        let stored = Preferences {
+            backend: Some(BackendKind::Unicode),
+            width_stretch: Some(1.5),
+            quiet: Some(true),
+            ..Preferences::default()
+        };
+
+        let cli = Cli::parse_from(["showme", "--backend", "kitty", "--width-stretch", "2.5", "-v", "img.png"]);
+        let config = cli.into_config_with(stored).expect("config");
+        assert_eq!(config.backend, BackendKind::Kitty);
+        assert_eq!(config.sizing.width_stretch, 2.5);
+        assert!(!config.quiet);
+    }
+
+    #[test]
+    fn pinned_geometry_drives_width_stretch() {
+        let cli = Cli::parse_from(["showme", "--term-size", "100x30", "--cell-size", "10x16", "img.png"]);
+        let config = cli.into_config_with(Preferences::default()).expect("config");
+        assert_eq!(config.term_size, Some((100, 30)));
+        assert_eq!(config.cell_size, Some((10, 16)));
+        assert_eq!(config.sizing.width_stretch, 1.6);
+
+        assert!(Cli::try_parse_from(["showme", "--term-size", "100", "img.png"]).is_err());
+        assert!(Cli::try_parse_from(["showme", "--cell-size", "0x16", "img.png"]).is_err());
+    }
 }
@@ -52,8 +52,7 @@ fn quantize_channel_to_6(value: u8) -> u8 {
     }
 }
 
-// Convert 256-color index back to approximate RGB (for testing)
-#[allow(dead_code)]
+// Convert 256-color index back to approximate RGB (used by tests and HTML export)
 pub fn color_256_to_rgb(index: u8) -> RgbColor {
     match index {
         // Grayscale ramp (232-255)
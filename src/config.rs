@@ -2,6 +2,8 @@ use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use std::str::FromStr;
 
+use crate::image::DecodeLimits;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BackendKind {
     Auto,
@@ -21,6 +23,27 @@ pub enum PixelationMode {
 pub enum RotationMode {
     Off,
     Exif,
+    /// Follow EXIF orientation, then turn clockwise by 90 degrees
+    Clockwise90,
+    Clockwise180,
+    Clockwise270,
+}
+
+impl RotationMode {
+    /// Whether the EXIF orientation tag is honoured
+    pub fn follows_exif(self) -> bool {
+        self != Self::Off
+    }
+
+    /// Clockwise quarter turns applied after EXIF orientation
+    pub fn quarter_turns(self) -> u8 {
+        match self {
+            Self::Off | Self::Exif => 0,
+            Self::Clockwise90 => 1,
+            Self::Clockwise180 => 2,
+            Self::Clockwise270 => 3,
+        }
+    }
 }
 
 impl Default for RotationMode {
@@ -36,14 +59,271 @@ impl FromStr for RotationMode {
         match value.to_ascii_lowercase().as_str() {
             "off" => Ok(Self::Off),
             "exif" => Ok(Self::Exif),
+            "90" => Ok(Self::Clockwise90),
+            "180" => Ok(Self::Clockwise180),
+            "270" | "-90" => Ok(Self::Clockwise270),
+            other => Err(format!(
+                "unsupported rotation mode '{}'. valid choices: exif, off, 90, 180, 270",
+                other
+            )),
+        }
+    }
+}
+
+/// Mirror images after rotation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flip {
+    /// Mirror left to right
+    Horizontal,
+    /// Mirror top to bottom
+    Vertical,
+    Both,
+}
+
+impl FromStr for Flip {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "h" | "horizontal" => Ok(Self::Horizontal),
+            "v" | "vertical" => Ok(Self::Vertical),
+            "hv" | "vh" | "both" => Ok(Self::Both),
+            other => Err(format!(
+                "unsupported flip '{}'. valid choices: h (horizontal), v (vertical), hv (both)",
+                other
+            )),
+        }
+    }
+}
+
+/// One side or offset of a [`CropRegion`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CropLength {
+    Pixels(u32),
+    /// Percentage of the image's width or height
+    Percent(f32),
+}
+
+impl CropLength {
+    fn resolve(self, full: u32) -> u32 {
+        match self {
+            Self::Pixels(pixels) => pixels,
+            Self::Percent(percent) => (full as f32 * percent / 100.0).round() as u32,
+        }
+    }
+}
+
+impl FromStr for CropLength {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.strip_suffix('%') {
+            Some(percent) => percent
+                .parse::<f32>()
+                .ok()
+                .filter(|percent| (0.0..=100.0).contains(percent))
+                .map(Self::Percent)
+                .ok_or_else(|| format!("invalid percentage '{}'; expected 0%-100%", value)),
+            None => value
+                .parse::<u32>()
+                .map(Self::Pixels)
+                .map_err(|_| format!("invalid length '{}'; expected pixels or a percentage", value)),
+        }
+    }
+}
+
+/// A rectangle to cut out of each image, parsed from `WxH[+X+Y]`
+///
+/// Every part is in pixels or, with a `%` suffix, relative to the image
+/// size; offsets default to the top-left corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CropRegion {
+    pub width: CropLength,
+    pub height: CropLength,
+    pub x: CropLength,
+    pub y: CropLength,
+}
+
+impl CropRegion {
+    /// The `(x, y, width, height)` pixel rectangle within a `width`×`height` image
+    ///
+    /// The region is clamped to the image; `None` when nothing of it is left.
+    pub fn resolve(&self, width: u32, height: u32) -> Option<(u32, u32, u32, u32)> {
+        let x = self.x.resolve(width);
+        let y = self.y.resolve(height);
+        let crop_width = self.width.resolve(width).min(width.saturating_sub(x));
+        let crop_height = self.height.resolve(height).min(height.saturating_sub(y));
+        (crop_width > 0 && crop_height > 0).then_some((x, y, crop_width, crop_height))
+    }
+}
+
+impl FromStr for CropRegion {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid crop region '{}'; expected WxH+X+Y, e.g. 50%x50%+25%+25%", value);
+        let mut parts = value.split('+');
+        let (width, height) = parts.next().and_then(|size| size.split_once('x')).ok_or_else(invalid)?;
+        let (x, y) = match (parts.next(), parts.next(), parts.next()) {
+            (None, None, None) => ("0", "0"),
+            (Some(x), Some(y), None) => (x, y),
+            _ => return Err(invalid()),
+        };
+        let region = Self {
+            width: width.parse()?,
+            height: height.parse()?,
+            x: x.parse()?,
+            y: y.parse()?,
+        };
+        if matches!(region.width, CropLength::Pixels(0)) || matches!(region.height, CropLength::Pixels(0)) {
+            return Err(invalid());
+        }
+        Ok(region)
+    }
+}
+
+/// Curve that compresses HDR images (EXR, Radiance HDR) into displayable range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ToneMapOperator {
+    /// Scale by the exposure and clip at white
+    Clip,
+    Reinhard,
+    #[default]
+    Aces,
+    Hable,
+}
+
+impl FromStr for ToneMapOperator {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "clip" | "none" => Ok(Self::Clip),
+            "reinhard" => Ok(Self::Reinhard),
+            "aces" => Ok(Self::Aces),
+            "hable" | "filmic" => Ok(Self::Hable),
             other => Err(format!(
-                "unsupported rotation mode '{}'. valid choices: exif, off",
+                "unsupported tone-map operator '{}'. valid choices: aces, reinhard, hable (filmic), clip (none)",
                 other
             )),
         }
     }
 }
 
+/// Resampling kernel used when scaling images to the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResampleFilter {
+    Nearest,
+    Triangle,
+    CatmullRom,
+    Lanczos3,
+    /// Box average over each source area; only differs from triangle when shrinking
+    Area,
+}
+
+impl FromStr for ResampleFilter {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "nearest" => Ok(Self::Nearest),
+            "triangle" | "bilinear" => Ok(Self::Triangle),
+            "catmull-rom" | "catmullrom" | "cubic" => Ok(Self::CatmullRom),
+            "lanczos3" | "lanczos" => Ok(Self::Lanczos3),
+            "area" | "box" => Ok(Self::Area),
+            other => Err(format!(
+                "unsupported filter '{}'. valid choices: nearest, triangle, catmull-rom, lanczos3, area (box)",
+                other
+            )),
+        }
+    }
+}
+
+/// Document pages picked with `--pages 1,3,10-12`; numbers are 1-based
+///
+/// A range without an end (`10-`) runs to the last page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageSelection {
+    ranges: Vec<(usize, Option<usize>)>,
+}
+
+impl PageSelection {
+    /// Zero-based indices of the selected pages of a `count`-page document
+    ///
+    /// Pages keep the order they were listed in; repeats and pages past
+    /// the end are dropped.
+    pub fn indices(&self, count: usize) -> Vec<usize> {
+        let mut seen = vec![false; count];
+        let mut indices = Vec::new();
+        for &(first, last) in &self.ranges {
+            let last = last.unwrap_or(count).min(count);
+            for page in first..=last {
+                if !std::mem::replace(&mut seen[page - 1], true) {
+                    indices.push(page - 1);
+                }
+            }
+        }
+        indices
+    }
+}
+
+impl FromStr for PageSelection {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = |part: &str| format!("invalid page range '{}'; expected e.g. 1,3,10-12 or 5-", part);
+        let page = |number: &str, part: &str| match number.trim().parse::<usize>() {
+            Ok(page) if page > 0 => Ok(page),
+            _ => Err(invalid(part)),
+        };
+        let ranges = value
+            .split(',')
+            .map(|part| match part.split_once('-') {
+                Some((first, "")) => Ok((page(first, part)?, None)),
+                Some((first, last)) => {
+                    let (first, last) = (page(first, part)?, page(last, part)?);
+                    if first > last {
+                        return Err(invalid(part));
+                    }
+                    Ok((first, Some(last)))
+                }
+                None => page(part, part).map(|page| (page, Some(page))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { ranges })
+    }
+}
+
+/// Unsharp mask parameters, parsed from `SIGMA[,THRESHOLD]`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnsharpMask {
+    /// Blur radius (Gaussian sigma) in pixels
+    pub sigma: f32,
+    /// Minimum brightness difference, 0-255, that gets sharpened
+    pub threshold: i32,
+}
+
+impl FromStr for UnsharpMask {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (sigma, threshold) = value.split_once(',').unwrap_or((value, "0"));
+        let sigma = sigma
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|sigma| sigma.is_finite() && *sigma > 0.0)
+            .ok_or_else(|| format!("invalid sharpen radius '{}'; expected a positive number", sigma))?;
+        let threshold = threshold
+            .trim()
+            .parse::<i32>()
+            .ok()
+            .filter(|threshold| (0..=255).contains(threshold))
+            .ok_or_else(|| format!("invalid sharpen threshold '{}'; expected 0-255", threshold))?;
+        Ok(Self { sigma, threshold })
+    }
+}
+
 impl Default for PixelationMode {
     fn default() -> Self {
         Self::Quarter
@@ -65,6 +345,68 @@ impl FromStr for PixelationMode {
     }
 }
 
+/// Order of the files found when expanding a directory input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    /// Natural file name order (`img2` before `img10`)
+    #[default]
+    Name,
+    /// Oldest modification time first
+    Mtime,
+    /// Smallest file first
+    Size,
+    /// EXIF capture date, falling back to modification time
+    Exif,
+    /// Shuffled, reproducibly when a seed is given
+    Random,
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "name" => Ok(Self::Name),
+            "mtime" | "time" => Ok(Self::Mtime),
+            "size" => Ok(Self::Size),
+            "exif" | "date" => Ok(Self::Exif),
+            "random" | "shuffle" => Ok(Self::Random),
+            other => Err(format!(
+                "unsupported sort order '{}'. valid choices: name, mtime, size, exif, random",
+                other
+            )),
+        }
+    }
+}
+
+/// Encoding of the rendered output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Terminal escape sequences
+    #[default]
+    Ansi,
+    /// A standalone HTML page
+    Html,
+    /// A PNG of the Unicode block art as the terminal would show it
+    Png,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "ansi" | "terminal" => Ok(Self::Ansi),
+            "html" => Ok(Self::Html),
+            "png" => Ok(Self::Png),
+            other => Err(format!(
+                "unsupported output format '{}'. valid choices: ansi, html, png",
+                other
+            )),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RgbColor {
     pub r: u8,
@@ -114,6 +456,10 @@ pub struct RenderSizing {
     pub upscale_integer: bool,
     pub width_stretch: f32,
     pub antialias: bool,
+    /// Filter to scale with; `None` lets each backend pick its default
+    pub filter: Option<ResampleFilter>,
+    /// Resample in linear light instead of on sRGB-encoded values
+    pub linear_light: bool,
 }
 
 impl RenderSizing {
@@ -127,6 +473,8 @@ impl RenderSizing {
             upscale_integer: false,
             width_stretch: 2.0, // Most terminal fonts are ~2x taller than wide, so stretch width by 2x
             antialias: true,
+            filter: None,
+            linear_light: false,
         }
     }
 }
@@ -183,8 +531,11 @@ pub struct Config {
     pub pattern_color: Option<RgbColor>,
     pub pattern_size: u16,
     pub auto_crop: bool,
+    /// Per-channel difference from the background color still trimmed by auto-crop
+    pub auto_crop_tolerance: u8,
     pub crop_border: u32,
     pub output_file: Option<PathBuf>,
+    pub output_format: OutputFormat,
     pub use_8bit_color: bool,
     pub threads: Option<usize>,
     pub compress_level: u8,
@@ -194,6 +545,38 @@ pub struct Config {
     pub scroll_delay: std::time::Duration,
     pub scroll_dx: i32,
     pub scroll_dy: i32,
+    /// Pinned terminal size in cells (columns, rows); skips size detection
+    pub term_size: Option<(u16, u16)>,
+    /// Pinned cell size in pixels (width, height); skips the pixel-size query
+    pub cell_size: Option<(u16, u16)>,
+    /// Record playback as an asciicast v2 file instead of drawing it
+    pub record: Option<PathBuf>,
+    /// Resource caps applied while decoding each input
+    pub decode_limits: DecodeLimits,
+    /// Operator used to display HDR images
+    pub tone_map: ToneMapOperator,
+    /// Exposure adjustment for HDR images, in stops
+    pub exposure: f32,
+    /// Convert images with an embedded ICC profile to sRGB
+    pub color_management: bool,
+    /// Brightness change in percent of full scale (-100 to 100)
+    pub brightness: f32,
+    /// Contrast change in percent (-100 flattens to gray)
+    pub contrast: f32,
+    /// Gamma correction; 1.0 leaves images unchanged
+    pub gamma: f32,
+    /// Saturation factor; 1.0 leaves images unchanged
+    pub saturation: f32,
+    pub grayscale: bool,
+    pub invert: bool,
+    /// Unsharp mask applied after the other adjustments
+    pub sharpen: Option<UnsharpMask>,
+    /// Mirror applied after rotation
+    pub flip: Option<Flip>,
+    /// Region cut out after rotation and flipping, before border cropping
+    pub crop: Option<CropRegion>,
+    /// PDF pages to show; `None` shows every page
+    pub pages: Option<PageSelection>,
 }
 
 impl Config {
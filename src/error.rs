@@ -1,4 +1,6 @@
+use std::fmt;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use thiserror::Error;
 
@@ -28,6 +30,12 @@ pub enum RimgError {
         source: image::ImageError,
     },
 
+    #[error("refusing to decode {path}: {limit}")]
+    LimitExceeded { path: PathBuf, limit: Limit },
+
+    #[error("interrupted by signal {signal}")]
+    Interrupted { signal: i32 },
+
     #[error(transparent)]
     Io(#[from] std::io::Error),
 
@@ -39,4 +47,32 @@ impl RimgError {
     pub fn other(msg: impl Into<String>) -> Self {
         Self::Other(msg.into())
     }
+
+    /// Process exit status for this error (128 + signal number for interruptions)
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Interrupted { signal } => 128 + signal,
+            _ => 1,
+        }
+    }
+}
+
+/// A decoder resource limit, with the configured maximum that was exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Pixels(u64),
+    Bytes(u64),
+    Frames(usize),
+    DecodeTime(Duration),
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pixels(max) => write!(f, "more than {max} pixels"),
+            Self::Bytes(max) => write!(f, "larger than {max} bytes"),
+            Self::Frames(max) => write!(f, "more than {max} frames"),
+            Self::DecodeTime(max) => write!(f, "decoding took longer than {max:?}"),
+        }
+    }
 }
@@ -0,0 +1,216 @@
+//! Conversion of rendered terminal output into a standalone HTML page
+//!
+//! The renderer keeps producing the same escape sequences it would send to a
+//! terminal; [`HtmlWriter`] collects them and translates colour SGR codes
+//! into `<span>`s inside a `<pre>`. Inline images in the iTerm2 `File=`
+//! format become `<img>` data URIs, and every other control sequence (cursor
+//! movement, screen clears) is dropped.
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use crate::ansi::SgrState;
+use crate::error::Result;
+
+const PAGE_HEAD: &str = "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>showme</title>
+<style>
+body { background: #000; color: #ccc; margin: 1em; }
+pre { font-family: \"DejaVu Sans Mono\", Menlo, Consolas, monospace; font-size: 14px; line-height: 1; margin: 0; }
+pre img { vertical-align: top; }
+</style>
+</head>
+<body>
+<pre>";
+
+const PAGE_TAIL: &str = "</pre>
+</body>
+</html>
+";
+
+/// Buffers terminal output and writes it to `inner` as HTML on [`finish`](Self::finish)
+pub struct HtmlWriter<W: Write> {
+    inner: W,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> HtmlWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Convert everything written so far and emit the complete page
+    pub fn finish(mut self) -> Result<()> {
+        let text = String::from_utf8_lossy(&self.buffer);
+        self.inner.write_all(PAGE_HEAD.as_bytes())?;
+        self.inner.write_all(ansi_to_html(&text).as_bytes())?;
+        self.inner.write_all(PAGE_TAIL.as_bytes())?;
+        self.inner.flush()?;
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for HtmlWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    /// The page can only be produced once all output is known, so flushing is a no-op
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn style_css(style: &SgrState) -> Option<String> {
+    let mut css = String::new();
+    if let Some([r, g, b]) = style.fg {
+        let _ = write!(css, "color:#{:02x}{:02x}{:02x};", r, g, b);
+    }
+    if let Some([r, g, b]) = style.bg {
+        let _ = write!(css, "background-color:#{:02x}{:02x}{:02x};", r, g, b);
+    }
+    (!css.is_empty()).then_some(css)
+}
+
+/// Translate terminal output into the HTML that goes inside a `<pre>`
+pub fn ansi_to_html(text: &str) -> String {
+    let mut html = String::with_capacity(text.len());
+    let mut style = SgrState::default();
+    let mut open: Option<SgrState> = None;
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\x1b' {
+            if open != Some(style) {
+                if open.is_some_and(|span| style_css(&span).is_some()) {
+                    html.push_str("</span>");
+                }
+                if let Some(css) = style_css(&style) {
+                    let _ = write!(html, "<span style=\"{}\">", css);
+                }
+                open = Some(style);
+            }
+            push_escaped(&mut html, ch);
+            continue;
+        }
+
+        match chars.next() {
+            // CSI: parameters up to a final byte in 0x40..=0x7e
+            Some('[') => {
+                let mut params = String::new();
+                for next in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&next) {
+                        if next == 'm' {
+                            style.apply(&params);
+                        }
+                        break;
+                    }
+                    params.push(next);
+                }
+            }
+            // OSC: terminated by BEL or ST
+            Some(']') => {
+                let body = take_until_terminator(&mut chars);
+                if let Some(image) = inline_image(&body) {
+                    if open.is_some_and(|span| style_css(&span).is_some()) {
+                        html.push_str("</span>");
+                    }
+                    open = None;
+                    html.push_str(&image);
+                }
+            }
+            // APC (Kitty graphics) and DCS (sixel, tmux passthrough) have no HTML form
+            Some('_') | Some('P') => {
+                take_until_terminator(&mut chars);
+            }
+            _ => {}
+        }
+    }
+
+    if open.is_some_and(|span| style_css(&span).is_some()) {
+        html.push_str("</span>");
+    }
+    html
+}
+
+fn take_until_terminator(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> String {
+    let mut body = String::new();
+    while let Some(next) = chars.next() {
+        match next {
+            '\x07' => break,
+            '\x1b' if chars.peek() == Some(&'\\') => {
+                chars.next();
+                break;
+            }
+            other => body.push(other),
+        }
+    }
+    body
+}
+
+/// `<img>` for an iTerm2 `1337;File=args:base64` sequence
+fn inline_image(body: &str) -> Option<String> {
+    let (args, data) = body.strip_prefix("1337;File=")?.split_once(':')?;
+    let width = args
+        .split(';')
+        .find_map(|arg| arg.strip_prefix("width="))
+        .and_then(|width| width.parse::<u32>().ok());
+
+    let style = width.map(|cells| format!(" style=\"width:{}ch\"", cells)).unwrap_or_default();
+    Some(format!("<img alt=\"\"{} src=\"data:image/png;base64,{}\">", style, data))
+}
+
+fn push_escaped(html: &mut String, ch: char) {
+    match ch {
+        '&' => html.push_str("&amp;"),
+        '<' => html.push_str("&lt;"),
+        '>' => html.push_str("&gt;"),
+        '"' => html.push_str("&quot;"),
+        // Carriage returns and other controls would only confuse the layout
+        '\r' => {}
+        other if other.is_control() && other != '\n' => {}
+        other => html.push(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_truecolor_and_palette_sgr_to_spans() {
+        let html = ansi_to_html("\x1b[38;2;255;0;0m\x1b[48;5;16m▀\x1b[0m <b>\n");
+        assert_eq!(
+            html,
+            "<span style=\"color:#ff0000;background-color:#000000;\">▀</span> &lt;b&gt;\n"
+        );
+    }
+
+    #[test]
+    fn drops_cursor_movement_and_keeps_inline_images() {
+        let html = ansi_to_html("\x1b[2J\x1b[H\x1b]1337;File=inline=1;width=12;height=4:AAAA\x07\n");
+        assert_eq!(
+            html,
+            "<img alt=\"\" style=\"width:12ch\" src=\"data:image/png;base64,AAAA\">\n"
+        );
+    }
+
+    #[test]
+    fn writer_wraps_output_in_a_page() {
+        let mut out = Vec::new();
+        let mut writer = HtmlWriter::new(&mut out);
+        writer.write_all(b"# 1 - a&b.png\n").unwrap();
+        writer.finish().unwrap();
+
+        let page = String::from_utf8(out).unwrap();
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<pre># 1 - a&amp;b.png\n</pre>"));
+    }
+}
@@ -0,0 +1,253 @@
+//! Colour management with embedded ICC profiles
+//!
+//! Terminals show sRGB, so pixels tagged with another profile (Display P3
+//! from phones, Adobe RGB from cameras, print CMYK) are converted through
+//! the profile before they become a [`Frame`](crate::image::Frame). RGB and
+//! gray profiles are applied to the decoded image. CMYK JPEGs and TIFFs
+//! need their raw ink values, which the `image` crate has already flattened
+//! to RGB without a profile, so [`decode_cmyk_jpeg`] and
+//! [`decode_cmyk_tiff`] decode them again.
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use image::{DynamicImage, RgbaImage};
+use moxcms::{ColorProfile, DataColorSpace, Layout, Transform8BitExecutor, TransformOptions};
+use tiff::decoder::{Decoder as TiffDecoder, DecodingResult};
+use zune_core::bytestream::ZCursor;
+use zune_core::colorspace::ColorSpace;
+use zune_core::options::DecoderOptions;
+use zune_jpeg::JpegDecoder;
+
+use crate::tonemap;
+
+/// A conversion from an embedded profile to sRGB
+#[derive(Clone)]
+pub struct IccTransform {
+    transform: Arc<Transform8BitExecutor>,
+    source: DataColorSpace,
+}
+
+impl std::fmt::Debug for IccTransform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IccTransform").field("source", &self.source).finish()
+    }
+}
+
+impl IccTransform {
+    /// Parse `icc`; `None` when it is malformed or not an RGB, gray or CMYK profile
+    pub fn new(icc: &[u8]) -> Option<Self> {
+        let profile = ColorProfile::new_from_slice(icc).ok()?;
+        let layout = match profile.color_space {
+            DataColorSpace::Rgb => Layout::Rgba,
+            DataColorSpace::Gray => Layout::GrayAlpha,
+            // Four-channel CMYK shares the RGBA layout
+            DataColorSpace::Cmyk => Layout::Rgba,
+            _ => return None,
+        };
+        let transform = profile
+            .create_transform_8bit(layout, &ColorProfile::new_srgb(), Layout::Rgba, TransformOptions::default())
+            .ok()?;
+        Some(Self {
+            transform,
+            source: profile.color_space,
+        })
+    }
+
+    /// Whether the profile describes CMYK ink values rather than display colours
+    pub fn is_cmyk(&self) -> bool {
+        self.source == DataColorSpace::Cmyk
+    }
+
+    /// Convert a decoded image to sRGB
+    ///
+    /// Float images (already linear) and CMYK profiles, which can't apply to
+    /// RGB data, leave the image untouched.
+    pub fn apply(&self, image: DynamicImage) -> DynamicImage {
+        if tonemap::is_hdr(&image) || self.is_cmyk() {
+            return image;
+        }
+        let (width, height) = (image.width(), image.height());
+        let source = match self.source {
+            DataColorSpace::Gray => image.to_luma_alpha8().into_raw(),
+            _ => image.to_rgba8().into_raw(),
+        };
+        self.run(&source, width, height).map_or(image, DynamicImage::ImageRgba8)
+    }
+
+    /// Convert an RGBA frame in place
+    pub fn apply_rgba(&self, pixels: &mut RgbaImage) {
+        if self.source != DataColorSpace::Rgb {
+            return;
+        }
+        if let Some(converted) = self.run(pixels.as_raw(), pixels.width(), pixels.height()) {
+            *pixels = converted;
+        }
+    }
+
+    fn run(&self, source: &[u8], width: u32, height: u32) -> Option<RgbaImage> {
+        let mut out = vec![0; width as usize * height as usize * 4];
+        self.transform.transform(source, &mut out).ok()?;
+        RgbaImage::from_raw(width, height, out)
+    }
+}
+
+/// Decode a CMYK or YCCK JPEG and convert its inks to sRGB through `transform`
+///
+/// Adobe applications store inverted ink values, and every CMYK JPEG
+/// decoder assumes they do, so the samples are flipped back before the
+/// profile sees them.
+pub fn decode_cmyk_jpeg(bytes: &[u8], transform: &IccTransform) -> Option<RgbaImage> {
+    if !transform.is_cmyk() {
+        return None;
+    }
+    let options = DecoderOptions::default().set_strict_mode(false);
+    let mut decoder = JpegDecoder::new_with_options(ZCursor::new(bytes), options);
+    decoder.decode_headers().ok()?;
+    let input = decoder.input_colorspace()?;
+    if !matches!(input, ColorSpace::CMYK | ColorSpace::YCCK) {
+        return None;
+    }
+    // Asking for the input colour space returns the four planes untouched
+    decoder.set_options(decoder.options().jpeg_set_out_colorspace(input));
+    let (width, height) = decoder.dimensions()?;
+    let mut samples = decoder.decode().ok()?;
+
+    for pixel in samples.chunks_exact_mut(4) {
+        if input == ColorSpace::YCCK {
+            let [r, g, b] = ycc_to_rgb(pixel[0], pixel[1], pixel[2]);
+            pixel[..3].copy_from_slice(&[r, g, b]);
+        }
+        for sample in pixel.iter_mut() {
+            *sample = 255 - *sample;
+        }
+    }
+    transform.run(&samples, width as u32, height as u32)
+}
+
+/// The ICC profile embedded in a TIFF
+///
+/// The `image` crate asks for `Tag::Unknown(34675)`, which the `tiff` crate
+/// now parses as `Tag::IccProfile`, so it never finds the profile.
+pub fn tiff_profile(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut decoder = TiffDecoder::new(Cursor::new(bytes)).ok()?;
+    decoder.get_tag_u8_vec(tiff::tags::Tag::IccProfile).ok()
+}
+
+/// Decode an 8 or 16-bit CMYK TIFF and convert its inks to sRGB through `transform`
+///
+/// TIFF stores ink coverage directly (0 is bare paper), as the profile
+/// expects, and 16-bit samples are reduced to 8 bits first. CMYK with an
+/// extra alpha channel is left to the `image` crate.
+pub fn decode_cmyk_tiff(bytes: &[u8], transform: &IccTransform) -> Option<RgbaImage> {
+    if !transform.is_cmyk() {
+        return None;
+    }
+    let mut decoder = TiffDecoder::new(Cursor::new(bytes)).ok()?;
+    if !matches!(decoder.colortype().ok()?, tiff::ColorType::CMYK(8 | 16)) {
+        return None;
+    }
+    let (width, height) = decoder.dimensions().ok()?;
+    let samples = match decoder.read_image().ok()? {
+        DecodingResult::U8(samples) => samples,
+        DecodingResult::U16(samples) => samples.iter().map(|&sample| (sample >> 8) as u8).collect(),
+        _ => return None,
+    };
+    transform.run(&samples, width, height)
+}
+
+/// JFIF YCbCr to RGB, which YCCK uses for the inverted C, M and Y planes
+fn ycc_to_rgb(y: u8, cb: u8, cr: u8) -> [u8; 3] {
+    let (y, cb, cr) = (f32::from(y), f32::from(cb) - 128.0, f32::from(cr) - 128.0);
+    [
+        y + 1.402 * cr,
+        y - 0.344_136 * cb - 0.714_136 * cr,
+        y + 1.772 * cb,
+    ]
+    .map(|value| value.round().clamp(0.0, 255.0) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use moxcms::ColorProfile;
+
+    #[test]
+    fn converts_display_p3_red_into_srgb() {
+        let p3 = ColorProfile::new_display_p3().encode().expect("encode profile");
+        let transform = IccTransform::new(&p3).expect("transform");
+        assert!(!transform.is_cmyk());
+
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_pixel(1, 1, image::Rgba([255, 0, 0, 128])));
+        let converted = transform.apply(image).to_rgba8();
+        let [r, g, b, a] = converted.get_pixel(0, 0).0;
+        // P3 red lies outside sRGB, so it clips to full red and pulls green/blue down to zero
+        assert_eq!((r, a), (255, 128));
+        assert!(g < 10 && b < 10, "{:?}", converted.get_pixel(0, 0));
+
+        let gray = DynamicImage::ImageRgba8(RgbaImage::from_pixel(1, 1, image::Rgba([128, 128, 128, 255])));
+        let [r, g, b, _] = transform.apply(gray).to_rgba8().get_pixel(0, 0).0;
+        assert!(r.abs_diff(128) <= 1 && r == g && g == b);
+    }
+
+    /// A CMYK printer profile that maps every ink mix to a saturated red
+    fn red_cmyk_profile() -> Vec<u8> {
+        use moxcms::{LutDataType, LutStore, LutType, LutWarehouse, Matrix3d, ProfileClass};
+
+        // lut16 Lab encoding of L 54, a 80, b 70
+        let red: [u16; 3] = [35251, (80 + 128) * 256, (70 + 128) * 256];
+        let lut = LutDataType {
+            num_input_channels: 4,
+            num_output_channels: 3,
+            num_clut_grid_points: 2,
+            matrix: Matrix3d::IDENTITY,
+            num_input_table_entries: 2,
+            num_output_table_entries: 2,
+            input_table: LutStore::Store16([0, 65535].repeat(4)),
+            clut_table: LutStore::Store16(red.repeat(16)),
+            output_table: LutStore::Store16([0, 65535].repeat(3)),
+            lut_type: LutType::Lut16,
+        };
+        let mut profile = ColorProfile::default();
+        profile.pcs = DataColorSpace::Lab;
+        profile.color_space = DataColorSpace::Cmyk;
+        profile.profile_class = ProfileClass::OutputDevice;
+        profile.white_point = ColorProfile::new_srgb().white_point;
+        profile.lut_a_to_b_perceptual = Some(LutWarehouse::Lut(lut));
+        profile.encode().expect("encode profile")
+    }
+
+    #[test]
+    fn converts_cmyk_tiff_inks_through_the_profile() {
+        use crate::image::{LoadOptions, load_image_from_bytes};
+        use std::path::Path;
+        use tiff::encoder::{TiffEncoder, colortype::CMYK8};
+        use tiff::tags::Tag;
+
+        let mut bytes = Cursor::new(Vec::new());
+        {
+            let mut encoder = TiffEncoder::new(&mut bytes).expect("tiff encoder");
+            let mut image = encoder.new_image::<CMYK8>(2, 2).expect("tiff image");
+            image
+                .encoder()
+                .write_tag(Tag::IccProfile, red_cmyk_profile().as_slice())
+                .expect("icc tag");
+            image.write_data(&[0; 16]).expect("tiff data");
+        }
+        let bytes = bytes.into_inner();
+
+        // Bare paper through the profile is red, where the naive conversion gives white
+        let managed = load_image_from_bytes(&bytes, Path::new("proof.tif"), &LoadOptions::default()).expect("decode");
+        let [r, g, b, a] = managed.frames[0].pixels.get_pixel(0, 0).0;
+        assert!(r > 200 && g < 80 && b < 80 && a == 255, "{:?}", [r, g, b, a]);
+
+        let plain = LoadOptions::default().with_color_management(false);
+        let naive = load_image_from_bytes(&bytes, Path::new("proof.tif"), &plain).expect("decode");
+        assert_eq!(naive.frames[0].pixels.get_pixel(0, 0).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn rejects_garbage_profiles() {
+        assert!(IccTransform::new(b"not a profile").is_none());
+    }
+}
@@ -1,17 +1,27 @@
 use std::fs::File;
 use std::io::{BufReader, Cursor, Read};
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+#[cfg(any(feature = "pdf", feature = "svg"))]
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use image::codecs::gif::GifDecoder;
-use image::{AnimationDecoder, DynamicImage, ImageFormat, ImageReader, RgbaImage};
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::{AnimationDecoder, DynamicImage, Frames, ImageDecoder, ImageFormat, ImageReader, RgbaImage};
 
+use crate::adjust::Adjustments;
+#[cfg(feature = "archive")]
+use crate::archive;
 use crate::autocrop;
-use crate::config::RotationMode;
-use crate::error::{Result, RimgError};
+use crate::config::{CropRegion, Flip, PageSelection, RotationMode};
+use crate::error::{Limit, Result, RimgError};
+use crate::icc::{self, IccTransform};
+use crate::raw;
+use crate::tonemap::{self, ToneMapping};
 
 #[cfg(feature = "video")]
-use crate::video::{VideoLoader, load_video};
+use crate::video::{VideoLoader, load_video_frames};
 
 #[derive(Debug, Clone)]
 pub struct Frame {
@@ -32,6 +42,8 @@ impl Frame {
 pub struct ImageSequence {
     pub path: PathBuf,
     pub frames: Vec<Frame>,
+    /// Page number (1-based) and page count when this is one page of a document
+    pub page: Option<(usize, usize)>,
 }
 
 impl ImageSequence {
@@ -40,175 +52,764 @@ impl ImageSequence {
     }
 }
 
+/// Decoding options shared by the path, stdin and in-memory loaders
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoadOptions {
+    /// Apply the EXIF orientation tag to still images
+    pub rotation: RotationMode,
+    /// Mirror applied after rotation
+    pub flip: Option<Flip>,
+    /// Region cut out after rotation and flipping
+    pub crop: Option<CropRegion>,
+    /// Trim uniform borders after `crop_border` is applied
+    pub auto_crop: bool,
+    /// Pixels to remove from every edge
+    pub crop_border: u32,
+    /// Per-channel difference from the background still trimmed by `auto_crop`
+    pub auto_crop_tolerance: u8,
+    /// Box in pixels the image will be drawn into, when known up front
+    ///
+    /// Loaders may then decode less: a large enough EXIF thumbnail instead
+    /// of the full JPEG, only the first frame of animations, videos and
+    /// PDFs, and stills are shrunk to fit right after decoding.
+    pub target_size: Option<(u32, u32)>,
+    /// Box in pixels vector input (PDF pages, SVGs) is rasterised to fit, when
+    /// `target_size` doesn't apply; derived from the terminal by the renderer
+    pub raster_size: Option<(u32, u32)>,
+    /// Resource caps for untrusted input
+    pub limits: DecodeLimits,
+    /// Conversion of float (HDR) images to 8 bits
+    pub tone_mapping: ToneMapping,
+    /// Convert images with an embedded ICC profile to sRGB
+    pub color_management: bool,
+    /// Colour and sharpness changes applied after orientation and cropping
+    pub adjustments: Adjustments,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self::new(RotationMode::default(), false, 0)
+    }
+}
+
+impl LoadOptions {
+    pub fn new(rotation: RotationMode, auto_crop: bool, crop_border: u32) -> Self {
+        Self {
+            rotation,
+            flip: None,
+            crop: None,
+            auto_crop,
+            crop_border,
+            auto_crop_tolerance: autocrop::DEFAULT_TOLERANCE,
+            target_size: None,
+            raster_size: None,
+            limits: DecodeLimits::default(),
+            tone_mapping: ToneMapping::default(),
+            color_management: true,
+            adjustments: Adjustments::default(),
+        }
+    }
+
+    /// Decode for display inside a `width`×`height` pixel box
+    pub fn with_target_size(mut self, width: u32, height: u32) -> Self {
+        self.target_size = Some((width.max(1), height.max(1)));
+        self
+    }
+
+    /// Mirror every frame after rotation
+    pub fn with_flip(mut self, flip: Option<Flip>) -> Self {
+        self.flip = flip;
+        self
+    }
+
+    /// Show only `crop` of every frame
+    pub fn with_crop(mut self, crop: Option<CropRegion>) -> Self {
+        self.crop = crop;
+        self
+    }
+
+    /// Treat colours up to `tolerance` per channel from the background as border when auto-cropping
+    pub fn with_auto_crop_tolerance(mut self, tolerance: u8) -> Self {
+        self.auto_crop_tolerance = tolerance;
+        self
+    }
+
+    /// Rasterise PDF pages and SVGs to fit a `width`×`height` pixel box; either may be `u32::MAX`
+    pub fn with_raster_size(mut self, width: u32, height: u32) -> Self {
+        self.raster_size = Some((width.max(1), height.max(1)));
+        self
+    }
+
+    /// Refuse inputs that exceed `limits`
+    pub fn with_limits(mut self, limits: DecodeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Display HDR images with `tone_mapping` instead of the default ACES curve
+    pub fn with_tone_mapping(mut self, tone_mapping: ToneMapping) -> Self {
+        self.tone_mapping = tone_mapping;
+        self
+    }
+
+    /// Whether embedded ICC profiles are honoured (on by default)
+    pub fn with_color_management(mut self, enabled: bool) -> Self {
+        self.color_management = enabled;
+        self
+    }
+
+    /// Brighten, sharpen or otherwise adjust every decoded frame
+    pub fn with_adjustments(mut self, adjustments: Adjustments) -> Self {
+        self.adjustments = adjustments;
+        self
+    }
+}
+
+/// Caps on the resources one input may use while decoding
+///
+/// Each limit is off when `None`. Exceeding one fails the input with
+/// [`RimgError::LimitExceeded`] instead of letting a decompression bomb
+/// allocate without bound. Pixel counts are checked against the header
+/// before any pixel buffer exists; decode time is checked between frames
+/// and pages, so a single still can't be interrupted once its decoder runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// Largest width × height of an image, frame or rendered page
+    pub max_pixels: Option<u64>,
+    /// Largest encoded input: a file, an archive entry or stdin
+    pub max_bytes: Option<u64>,
+    /// Most frames of an animation or video, or pages of a PDF
+    pub max_frames: Option<usize>,
+    /// Longest time spent decoding one input
+    pub max_decode_time: Option<Duration>,
+}
+
+impl DecodeLimits {
+    pub(crate) fn check_pixels(&self, width: u32, height: u32, path: &Path) -> Result<()> {
+        match self.max_pixels {
+            Some(max) if u64::from(width) * u64::from(height) > max => Err(exceeded(path, Limit::Pixels(max))),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_bytes(&self, len: u64, path: &Path) -> Result<()> {
+        match self.max_bytes {
+            Some(max) if len > max => Err(exceeded(path, Limit::Bytes(max))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fails once `count` frames have been seen and that is more than allowed
+    pub(crate) fn check_frames(&self, count: usize, path: &Path) -> Result<()> {
+        match self.max_frames {
+            Some(max) if count > max => Err(exceeded(path, Limit::Frames(max))),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_time(&self, started: Instant, path: &Path) -> Result<()> {
+        match self.max_decode_time {
+            Some(max) if started.elapsed() > max => Err(exceeded(path, Limit::DecodeTime(max))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Allocation caps for the `image` crate's decoders
+    fn image_limits(&self) -> image::Limits {
+        let mut limits = image::Limits::default();
+        // Room for 16-bit RGBA plus a converted copy
+        if let Some(max) = self.max_pixels {
+            limits.max_alloc = Some(max.saturating_mul(16));
+        }
+        limits
+    }
+
+    /// `err` from an `image` decoder, as a limit error when one of ours tripped it
+    fn image_error(&self, err: image::ImageError, path: &Path) -> RimgError {
+        match (err, self.max_pixels) {
+            (image::ImageError::Limits(_), Some(max)) => exceeded(path, Limit::Pixels(max)),
+            (err, _) => RimgError::ImageOpen {
+                path: path.to_path_buf(),
+                source: err,
+            },
+        }
+    }
+}
+
+fn exceeded(path: &Path, limit: Limit) -> RimgError {
+    RimgError::LimitExceeded {
+        path: path.to_path_buf(),
+        limit,
+    }
+}
+
+/// Load every image behind `path`
+///
+/// Archives (`.zip`, `.cbz`, `.tar`, `.cbt`) yield one sequence per image
+/// entry, in archive order, titled `archive.cbz:page01.png`. PDFs yield one
+/// sequence per page, rendered up front. Any other path yields the single sequence from
+/// [`load_image`].
+pub fn load_images(
+    path: &Path,
+    rotation_mode: RotationMode,
+    auto_crop: bool,
+    crop_border: u32,
+) -> Result<Vec<ImageSequence>> {
+    load_images_with(path, &LoadOptions::new(rotation_mode, auto_crop, crop_border))
+}
+
+/// [`load_images`] with full control over decoding
+pub fn load_images_with(path: &Path, options: &LoadOptions) -> Result<Vec<ImageSequence>> {
+    expand_input(path, options, None)?
+        .into_iter()
+        .map(|pending| pending.load(options))
+        .collect()
+}
+
+/// An image from an input, either decoded already or decoded when it is about to be shown
+#[derive(Debug, Clone)]
+pub enum PendingImage {
+    Ready(ImageSequence),
+    /// A PDF page, rendered by [`PendingImage::load`]
+    #[cfg(feature = "pdf")]
+    Page(DocumentPage),
+}
+
+impl PendingImage {
+    /// The decoded image, rendering it first if that was deferred
+    pub fn load(self, options: &LoadOptions) -> Result<ImageSequence> {
+        #[cfg(not(feature = "pdf"))]
+        let _ = options;
+        match self {
+            Self::Ready(sequence) => Ok(sequence),
+            #[cfg(feature = "pdf")]
+            Self::Page(page) => page.render(options),
+        }
+    }
+}
+
+/// One page of a PDF document held in memory
+#[cfg(feature = "pdf")]
+#[derive(Debug, Clone)]
+pub struct DocumentPage {
+    pub path: PathBuf,
+    /// Parsed once and shared by every page of the document
+    document: Arc<pdfium_render::prelude::PdfDocument<'static>>,
+    /// Zero-based page index
+    pub index: usize,
+    /// Number of pages in the document
+    pub count: usize,
+}
+
+#[cfg(feature = "pdf")]
+impl DocumentPage {
+    /// Render the page to fit `options.target_size` or `options.raster_size`
+    pub fn render(&self, options: &LoadOptions) -> Result<ImageSequence> {
+        let started = Instant::now();
+        let pixels = render_pdf_page(&self.document, self.index, &self.path, options)?;
+        options.limits.check_time(started, &self.path)?;
+        Ok(ImageSequence {
+            path: self.path.clone(),
+            frames: vec![Frame::single(pixels)],
+            page: Some((self.index + 1, self.count)),
+        })
+    }
+}
+
+/// Split `path` into the images it holds
+///
+/// Archive entries are decoded right away, like any other image. PDFs,
+/// whether named `.pdf`, recognised by their `%PDF-` header, piped to `-`
+/// or stored in an archive, yield a [`PendingImage::Page`] for each page
+/// in `pages` (all of them by default, or the first for a thumbnail) that
+/// is only rendered when loaded, so a long document costs nothing until its
+/// pages are shown.
+pub fn expand_input(
+    path: &Path,
+    options: &LoadOptions,
+    pages: Option<&PageSelection>,
+) -> Result<Vec<PendingImage>> {
+    #[cfg(feature = "archive")]
+    {
+        if archive::is_archive(path) {
+            return archive::read_image_entries_with(path, options.limits.max_bytes)?
+                .into_iter()
+                .map(|entry| {
+                    let name = PathBuf::from(format!("{}:{}", path.display(), entry.name));
+                    #[cfg(feature = "pdf")]
+                    {
+                        if entry.data.starts_with(b"%PDF-") {
+                            return document_pages(entry.data, &name, options, pages);
+                        }
+                    }
+                    load_image_from_bytes(&entry.data, &name, options).map(|sequence| vec![PendingImage::Ready(sequence)])
+                })
+                .collect::<Result<Vec<_>>>()
+                .map(|entries| entries.into_iter().flatten().collect());
+        }
+    }
+
+    #[cfg(feature = "pdf")]
+    {
+        let is_pdf = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+        if path.to_str() == Some("-") {
+            let data = read_stdin(&options.limits)?;
+            let name = Path::new("<stdin>");
+            if data.starts_with(b"%PDF-") {
+                return document_pages(data, name, options, pages);
+            }
+            return load_image_from_bytes(&data, name, options).map(|sequence| vec![PendingImage::Ready(sequence)]);
+        }
+        if is_pdf || has_pdf_header(path) {
+            return document_pages(read_file(path, &options.limits)?, path, options, pages);
+        }
+    }
+    #[cfg(not(feature = "pdf"))]
+    let _ = pages;
+
+    load_image_with(path, options).map(|sequence| vec![PendingImage::Ready(sequence)])
+}
+
+/// Load the image at `path`
+///
+/// An archive yields its first image entry; use [`load_images`] to read
+/// every entry.
 pub fn load_image(
     path: &Path,
     rotation_mode: RotationMode,
     auto_crop: bool,
     crop_border: u32,
 ) -> Result<ImageSequence> {
+    load_image_with(path, &LoadOptions::new(rotation_mode, auto_crop, crop_border))
+}
+
+/// [`load_image`] with full control over decoding
+pub fn load_image_with(path: &Path, options: &LoadOptions) -> Result<ImageSequence> {
     // Handle stdin as special case
     if path.to_str() == Some("-") {
-        return load_from_stdin(rotation_mode, auto_crop, crop_border);
+        return load_from_stdin(options);
     }
 
+    #[cfg(feature = "archive")]
+    {
+        if archive::is_archive(path) {
+            // An archive without images is an error, so there is a first entry
+            let entry = archive::read_image_entries_with(path, options.limits.max_bytes)?.swap_remove(0);
+            let name = PathBuf::from(format!("{}:{}", path.display(), entry.name));
+            return load_image_from_bytes(&entry.data, &name, options);
+        }
+    }
+
+    // Videos are streamed by ffmpeg instead of being read into memory
     #[cfg(feature = "video")]
     {
         if VideoLoader::is_video_candidate(path) {
-            if let Some(sequence) = load_video(path)? {
-                return Ok(sequence);
+            check_file_size(path, &options.limits)?;
+            if let Some(sequence) = load_video_frames(path, first_frame_only(options), &options.limits)? {
+                return Ok(finish_frames(sequence, options));
             }
         }
     }
 
-    let reader = ImageReader::open(path).map_err(|err| RimgError::ImageOpen {
+    let data = read_file(path, &options.limits)?;
+    load_image_from_bytes(&data, path, options)
+}
+
+fn file_error(path: &Path, err: std::io::Error) -> RimgError {
+    RimgError::ImageOpen {
         path: path.to_path_buf(),
         source: image::ImageError::IoError(err),
-    })?;
+    }
+}
 
-    let reader = reader
-        .with_guessed_format()
-        .map_err(|err| RimgError::ImageOpen {
-            path: path.to_path_buf(),
-            source: image::ImageError::IoError(err),
-        })?;
+/// Refuse files over the byte limit before reading them
+fn check_file_size(path: &Path, limits: &DecodeLimits) -> Result<()> {
+    if limits.max_bytes.is_some() {
+        let size = std::fs::metadata(path).map_err(|err| file_error(path, err))?.len();
+        limits.check_bytes(size, path)?;
+    }
+    Ok(())
+}
+
+fn read_file(path: &Path, limits: &DecodeLimits) -> Result<Vec<u8>> {
+    check_file_size(path, limits)?;
+    std::fs::read(path).map_err(|err| file_error(path, err))
+}
+
+fn read_stdin(limits: &DecodeLimits) -> Result<Vec<u8>> {
+    // Read one byte past the limit to tell a full-sized input from an oversized one
+    let cap = limits.max_bytes.map_or(u64::MAX, |max| max.saturating_add(1));
+    let mut buffer = Vec::new();
+    std::io::stdin()
+        .take(cap)
+        .read_to_end(&mut buffer)
+        .map_err(|err| RimgError::other(format!("failed to read from stdin: {}", err)))?;
+    limits.check_bytes(buffer.len() as u64, Path::new("<stdin>"))?;
+    Ok(buffer)
+}
+
+fn load_from_stdin(options: &LoadOptions) -> Result<ImageSequence> {
+    let buffer = read_stdin(&options.limits)?;
+    load_image_from_bytes(&buffer, Path::new("<stdin>"), options)
+}
+
+/// Decode an image that is already in memory
+///
+/// `hint` names the data: it becomes the sequence path, and its extension
+/// selects the video, QOI, PDF and SVG decoders. Without a recognised
+/// extension the format is sniffed from the content, video containers
+/// included. EXIF orientation is
+/// read from `bytes`, so piped-in photos are rotated like files on disk.
+pub fn load_image_from_bytes(bytes: &[u8], hint: &Path, options: &LoadOptions) -> Result<ImageSequence> {
+    let ext = hint
+        .extension()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_ascii_lowercase());
+    let ext = ext.as_deref();
+    options.limits.check_bytes(bytes.len() as u64, hint)?;
+
+    #[cfg(feature = "video")]
+    {
+        let container = video_container(bytes);
+        if container.is_some() || VideoLoader::is_video_candidate(hint) {
+            if let Some(sequence) = load_video_from_bytes(bytes, hint, container, options)? {
+                return Ok(sequence);
+            }
+        }
+    }
 
-    // Check for QOI format first (not supported by image crate's auto-detection)
     #[cfg(feature = "qoi")]
     {
-        if path.extension().and_then(|s| s.to_str()).map(|s| s.to_lowercase()) == Some("qoi".to_string()) {
-            return load_qoi(path, rotation_mode, auto_crop, crop_border);
+        if ext == Some("qoi") || bytes.starts_with(b"qoif") {
+            return load_qoi(bytes, hint, options);
         }
     }
 
-    // Check for PDF format
     #[cfg(feature = "pdf")]
     {
-        if path.extension().and_then(|s| s.to_str()).map(|s| s.to_lowercase()) == Some("pdf".to_string()) {
-            return load_pdf(path, rotation_mode, auto_crop, crop_border);
+        if ext == Some("pdf") || bytes.starts_with(b"%PDF-") {
+            return load_pdf(bytes, hint, options);
         }
     }
 
-    // Check for SVG format
     #[cfg(feature = "svg")]
     {
-        let ext = path.extension().and_then(|s| s.to_str()).map(|s| s.to_lowercase());
-        if ext == Some("svg".to_string()) || ext == Some("svgz".to_string()) {
-            return load_svg(path, rotation_mode, auto_crop, crop_border);
+        if matches!(ext, Some("svg" | "svgz")) || (ext.is_none() && looks_like_svg(bytes)) {
+            return load_svg(bytes, hint, options);
         }
     }
 
-    match reader.format() {
-        Some(ImageFormat::Gif) => load_gif(path, rotation_mode, auto_crop, crop_border),
-        Some(_) | None => load_static_image(reader.decode(), path, rotation_mode, auto_crop, crop_border),
+    if raw::is_raw(bytes, hint) {
+        return load_raw(bytes, hint, options);
     }
-}
 
-fn load_from_stdin(rotation_mode: RotationMode, auto_crop: bool, crop_border: u32) -> Result<ImageSequence> {
-    let mut buffer = Vec::new();
-    std::io::stdin()
-        .read_to_end(&mut buffer)
-        .map_err(|err| RimgError::other(format!("failed to read from stdin: {}", err)))?;
+    // Recognised even when their decoder is compiled out, to explain the failure
+    #[allow(unreachable_patterns)]
+    match ContainerFormat::detect(bytes) {
+        #[cfg(feature = "avif")]
+        Some(ContainerFormat::Avif) => return load_avif(bytes, hint, options),
+        #[cfg(feature = "heic")]
+        Some(ContainerFormat::Heic) => return load_heic(bytes, hint, options),
+        #[cfg(feature = "jxl")]
+        Some(ContainerFormat::Jxl) => return load_jxl(bytes, hint, options),
+        Some(format) => {
+            return Err(RimgError::other(format!(
+                "'{}' is {}, which needs showme built with the `{}` feature",
+                hint.display(),
+                format.name(),
+                format.feature()
+            )));
+        }
+        None => {}
+    }
 
-    let cursor = Cursor::new(buffer);
-    let reader = ImageReader::new(cursor)
-        .with_guessed_format()
-        .map_err(|err| RimgError::other(format!("failed to detect image format from stdin: {}", err)))?;
+    let mut reader = ImageReader::new(Cursor::new(bytes));
+    if let Ok(format) = ImageFormat::from_path(hint) {
+        reader.set_format(format);
+    }
+    let reader = reader.with_guessed_format().map_err(|err| {
+        RimgError::other(format!("failed to detect image format of '{}': {}", hint.display(), err))
+    })?;
+
+    let thumbnail = match reader.format() {
+        Some(ImageFormat::Jpeg) => load_exif_thumbnail(bytes, hint, options),
+        _ => None,
+    };
+    if let Some(sequence) = thumbnail {
+        return Ok(sequence);
+    }
 
-    let path = PathBuf::from("<stdin>");
     match reader.format() {
-        Some(ImageFormat::Gif) => {
-            // For GIF from stdin, need to recreate cursor
-            let data = reader.into_inner().into_inner();
-            let cursor = Cursor::new(data);
-            let decoder = GifDecoder::new(BufReader::new(cursor))
-                .map_err(|err| RimgError::ImageOpen {
-                    path: path.clone(),
-                    source: err,
-                })?;
-
-            let frames = decoder
-                .into_frames()
-                .collect_frames()
-                .map_err(|err| RimgError::FrameDecode {
-                    path: path.clone(),
-                    source: err,
-                })?;
-
-            let frames: Vec<Frame> = frames
-                .into_iter()
-                .map(|frame| {
-                    let delay = frame.delay();
-                    let (numer, denom) = delay.numer_denom_ms();
-                    let millis = if denom == 0 {
-                        0
-                    } else {
-                        ((numer as f32) / (denom as f32)).round() as u64
-                    };
-                    Frame {
-                        pixels: frame.into_buffer(),
-                        delay: Duration::from_millis(millis),
-                    }
-                })
-                .collect();
+        Some(ImageFormat::Gif) => load_gif(bytes, hint, options),
+        Some(ImageFormat::Png) if is_apng(bytes) => load_apng(bytes, hint, options),
+        Some(ImageFormat::WebP) if is_animated_webp(bytes) => load_animated_webp(bytes, hint, options),
+        format => {
+            let image = decode_still(bytes, format, hint, options)?;
+            let orientation = exif_orientation(bytes, options);
+            Ok(ImageSequence {
+                path: hint.to_path_buf(),
+                frames: vec![Frame::single(finish_still(image, orientation, options))],
+                page: None,
+            })
+        }
+    }
+}
 
-            Ok(ImageSequence { path, frames })
+/// Decode a still with the `image` crate, checking its header against the limits first
+///
+/// The result is converted to sRGB when it carries an ICC profile.
+fn decode_still(bytes: &[u8], format: Option<ImageFormat>, path: &Path, options: &LoadOptions) -> Result<DynamicImage> {
+    let limits = &options.limits;
+    let reader = || {
+        let mut reader = ImageReader::new(Cursor::new(bytes));
+        if let Some(format) = format {
+            reader.set_format(format);
         }
-        Some(_) | None => load_static_image(reader.decode(), &path, rotation_mode, auto_crop, crop_border),
+        reader.limits(limits.image_limits());
+        reader
+    };
+    if limits.max_pixels.is_some() {
+        let (width, height) = reader().into_dimensions().map_err(|err| limits.image_error(err, path))?;
+        limits.check_pixels(width, height, path)?;
     }
+
+    let mut decoder = reader().into_decoder().map_err(|err| limits.image_error(err, path))?;
+    let mut transform = icc_transform(&mut decoder, options);
+    if transform.is_none() && options.color_management && format == Some(ImageFormat::Tiff) {
+        transform = icc::tiff_profile(bytes).and_then(|profile| IccTransform::new(&profile));
+    }
+    let cmyk = transform.as_ref().and_then(|transform| match format {
+        Some(ImageFormat::Jpeg) => icc::decode_cmyk_jpeg(bytes, transform),
+        Some(ImageFormat::Tiff) => icc::decode_cmyk_tiff(bytes, transform),
+        _ => None,
+    });
+    if let Some(pixels) = cmyk {
+        return Ok(DynamicImage::ImageRgba8(pixels));
+    }
+    let image = DynamicImage::from_decoder(decoder).map_err(|err| limits.image_error(err, path))?;
+    Ok(match transform {
+        Some(transform) => transform.apply(image),
+        None => image,
+    })
 }
 
-fn load_static_image(
-    result: image::ImageResult<DynamicImage>,
-    path: &Path,
-    rotation_mode: RotationMode,
-    auto_crop: bool,
-    crop_border: u32,
-) -> Result<ImageSequence> {
-    let mut image = result.map_err(|err| RimgError::ImageOpen {
-        path: path.to_path_buf(),
-        source: err,
-    })?;
+/// The sRGB conversion for the ICC profile embedded in a decoder's image
+fn icc_transform(decoder: &mut impl ImageDecoder, options: &LoadOptions) -> Option<IccTransform> {
+    if !options.color_management {
+        return None;
+    }
+    let profile = decoder.icc_profile().ok()??;
+    IccTransform::new(&profile)
+}
 
-    // Apply EXIF orientation if enabled
-    if rotation_mode == RotationMode::Exif {
-        if let Some(orientation) = read_exif_orientation(path) {
-            image = apply_orientation(image, orientation);
-        }
+/// Apply tone mapping, EXIF orientation and cropping to a decoded still image
+fn finish_still(mut image: DynamicImage, orientation: Option<u32>, options: &LoadOptions) -> RgbaImage {
+    // HDR samples must be mapped while they are still floats
+    image = tonemap::apply(image, &options.tone_mapping);
+
+    if let Some(orientation) = orientation {
+        image = apply_orientation(image, orientation);
     }
+    image = apply_geometry(image, options);
 
     // Apply cropping: first fixed border, then auto-crop
-    if crop_border > 0 {
-        image = autocrop::crop_border(image, crop_border);
+    if options.crop_border > 0 {
+        image = autocrop::crop_border(image, options.crop_border);
     }
-    if auto_crop {
-        image = autocrop::auto_crop(image);
+    if options.auto_crop {
+        image = autocrop::auto_crop_with(image, options.auto_crop_tolerance);
     }
 
-    let rgba = image.to_rgba8();
-    Ok(ImageSequence {
+    // Nothing beyond the target box will ever be shown
+    let oversized = |&(width, height): &(u32, u32)| image.width() > width || image.height() > height;
+    if let Some((width, height)) = options.target_size.filter(oversized) {
+        image = image.thumbnail(width, height);
+    }
+
+    // Adjusting after the shrink keeps it cheap and sharpens at display size
+    options.adjustments.apply(image.into_rgba8())
+}
+
+/// Frame limit for loaders when only a preview of the first frame is needed
+fn first_frame_only(options: &LoadOptions) -> Option<usize> {
+    options.target_size.map(|_| 1)
+}
+
+/// The EXIF thumbnail of a JPEG, if it covers `options.target_size`
+///
+/// Thumbnails whose aspect ratio differs from the photo (some cameras pad
+/// them with black bars) and fixed border crops or crop regions, which are
+/// measured in full-size pixels, fall back to decoding the whole image.
+fn load_exif_thumbnail(bytes: &[u8], path: &Path, options: &LoadOptions) -> Option<ImageSequence> {
+    let (target_width, target_height) = options.target_size?;
+    if options.crop_border > 0 || options.crop.is_some() {
+        return None;
+    }
+
+    let exif = exif::Reader::new().read_from_container(&mut Cursor::new(bytes)).ok()?;
+    let thumbnail_field = |tag| {
+        exif.get_field(tag, exif::In::THUMBNAIL)
+            .and_then(|field| field.value.get_uint(0))
+            .map(|value| value as usize)
+    };
+    let offset = thumbnail_field(exif::Tag::JPEGInterchangeFormat)?;
+    let length = thumbnail_field(exif::Tag::JPEGInterchangeFormatLength)?;
+    let data = exif.buf().get(offset..offset.checked_add(length)?)?;
+    let plain = options.with_color_management(false);
+    let thumbnail = decode_still(data, Some(ImageFormat::Jpeg), path, &plain).ok()?;
+
+    let mut photo = ImageReader::with_format(Cursor::new(bytes), ImageFormat::Jpeg)
+        .into_decoder()
+        .ok()?;
+    let (width, height) = photo.dimensions();
+    // The thumbnail is encoded in the photo's colour space, tagged or not
+    let transform = icc_transform(&mut photo, options);
+    let aspect = |w: u32, h: u32| w as f32 / h.max(1) as f32;
+    if (aspect(thumbnail.width(), thumbnail.height()) / aspect(width, height) - 1.0).abs() > 0.02 {
+        return None;
+    }
+
+    let orientation = if options.rotation.follows_exif() {
+        orientation_tag(&exif)
+    } else {
+        None
+    };
+    let (shown_width, shown_height) = match orientation {
+        Some(5..=8) => (thumbnail.height(), thumbnail.width()),
+        _ => (thumbnail.width(), thumbnail.height()),
+    };
+    if shown_width < target_width && shown_height < target_height {
+        return None;
+    }
+    let thumbnail = match transform {
+        Some(transform) => transform.apply(thumbnail),
+        None => thumbnail,
+    };
+
+    Some(ImageSequence {
         path: path.to_path_buf(),
-        frames: vec![Frame::single(rgba)],
+        frames: vec![Frame::single(finish_still(thumbnail, orientation, options))],
+        page: None,
     })
 }
 
-fn load_gif(path: &Path, _rotation_mode: RotationMode, _auto_crop: bool, _crop_border: u32) -> Result<ImageSequence> {
+fn load_gif(bytes: &[u8], path: &Path, options: &LoadOptions) -> Result<ImageSequence> {
     // Note: EXIF rotation is not applied to GIF animations as they typically don't have EXIF data
-    // Note: Auto-crop is not applied to animations to maintain frame consistency
-    let file = File::open(path)?;
-    let decoder = GifDecoder::new(BufReader::new(file)).map_err(|err| RimgError::ImageOpen {
-        path: path.to_path_buf(),
-        source: err,
-    })?;
+    let open_error = |err| options.limits.image_error(err, path);
+    let mut decoder = GifDecoder::new(Cursor::new(bytes)).map_err(open_error)?;
+    limit_decoder(&mut decoder, path, &options.limits)?;
+    let sequence = collect_animation(decoder.into_frames(), path, options)?;
+    Ok(finish_animation(sequence, None, options))
+}
+
+fn is_apng(bytes: &[u8]) -> bool {
+    PngDecoder::new(Cursor::new(bytes))
+        .and_then(|decoder| decoder.is_apng())
+        .unwrap_or(false)
+}
 
-    let frames = decoder
-        .into_frames()
-        .collect_frames()
-        .map_err(|err| RimgError::FrameDecode {
-            path: path.to_path_buf(),
-            source: err,
+fn load_apng(bytes: &[u8], path: &Path, options: &LoadOptions) -> Result<ImageSequence> {
+    let open_error = |err| options.limits.image_error(err, path);
+    let mut decoder = PngDecoder::new(Cursor::new(bytes)).map_err(open_error)?;
+    limit_decoder(&mut decoder, path, &options.limits)?;
+    let transform = icc_transform(&mut decoder, options);
+    let decoder = decoder.apng().map_err(open_error)?;
+    let sequence = collect_animation(decoder.into_frames(), path, options)?;
+    Ok(finish_animation(sequence, transform, options))
+}
+
+fn is_animated_webp(bytes: &[u8]) -> bool {
+    WebPDecoder::new(Cursor::new(bytes))
+        .map(|decoder| decoder.has_animation())
+        .unwrap_or(false)
+}
+
+fn load_animated_webp(bytes: &[u8], path: &Path, options: &LoadOptions) -> Result<ImageSequence> {
+    let open_error = |err| options.limits.image_error(err, path);
+    let mut decoder = WebPDecoder::new(Cursor::new(bytes)).map_err(open_error)?;
+    limit_decoder(&mut decoder, path, &options.limits)?;
+    let transform = icc_transform(&mut decoder, options);
+    let sequence = collect_animation(decoder.into_frames(), path, options)?;
+    Ok(finish_animation(sequence, transform, options))
+}
+
+/// Convert animation frames to sRGB, then adjust them
+fn finish_animation(mut sequence: ImageSequence, transform: Option<IccTransform>, options: &LoadOptions) -> ImageSequence {
+    if let Some(transform) = transform {
+        for frame in &mut sequence.frames {
+            transform.apply_rgba(&mut frame.pixels);
+        }
+    }
+    finish_frames(sequence, options)
+}
+
+/// Rotate, flip, crop and adjust every frame of an animation or video
+///
+/// Auto-crop trims the union of all frames' content, so every frame keeps
+/// the same size and nothing that moves into the border is cut off.
+fn finish_frames(mut sequence: ImageSequence, options: &LoadOptions) -> ImageSequence {
+    let reshapes = options.rotation.quarter_turns() != 0
+        || options.flip.is_some()
+        || options.crop.is_some()
+        || options.crop_border > 0;
+    if reshapes {
+        for frame in &mut sequence.frames {
+            let mut image = apply_geometry(DynamicImage::ImageRgba8(std::mem::take(&mut frame.pixels)), options);
+            if options.crop_border > 0 {
+                image = autocrop::crop_border(image, options.crop_border);
+            }
+            frame.pixels = image.into_rgba8();
+        }
+    }
+
+    let bounds = if options.auto_crop {
+        autocrop::content_bounds(sequence.frames.iter().map(|frame| &frame.pixels), options.auto_crop_tolerance)
+    } else {
+        None
+    };
+    if let Some((x, y, width, height)) = bounds {
+        for frame in &mut sequence.frames {
+            frame.pixels = image::imageops::crop_imm(&frame.pixels, x, y, width, height).to_image();
+        }
+    }
+
+    if !options.adjustments.is_identity() {
+        for frame in &mut sequence.frames {
+            let pixels = std::mem::take(&mut frame.pixels);
+            frame.pixels = options.adjustments.apply(pixels);
+        }
+    }
+    sequence
+}
+
+/// Check an animation's canvas against `limits` and pass them on to its decoder
+fn limit_decoder(decoder: &mut impl ImageDecoder, path: &Path, limits: &DecodeLimits) -> Result<()> {
+    let (width, height) = decoder.dimensions();
+    limits.check_pixels(width, height, path)?;
+    decoder
+        .set_limits(limits.image_limits())
+        .map_err(|err| limits.image_error(err, path))
+}
+
+/// Decode the frames of an animation and convert the delays to `Duration`
+fn collect_animation(frames: Frames<'_>, path: &Path, options: &LoadOptions) -> Result<ImageSequence> {
+    let limit = first_frame_only(options).unwrap_or(usize::MAX);
+    let started = Instant::now();
+    let mut decoded = Vec::new();
+    for frame in frames.take(limit) {
+        let frame = frame.map_err(|err| match options.limits.image_error(err, path) {
+            RimgError::ImageOpen { path, source } => RimgError::FrameDecode { path, source },
+            err => err,
         })?;
+        decoded.push(frame);
+        options.limits.check_frames(decoded.len(), path)?;
+        options.limits.check_time(started, path)?;
+    }
 
-    let frames: Vec<Frame> = frames
+    let frames: Vec<Frame> = decoded
         .into_iter()
         .map(|frame| {
             let delay = frame.delay();
@@ -228,18 +829,112 @@ fn load_gif(path: &Path, _rotation_mode: RotationMode, _auto_crop: bool, _crop_b
     Ok(ImageSequence {
         path: path.to_path_buf(),
         frames,
+        page: None,
     })
 }
 
-#[cfg(feature = "qoi")]
-fn load_qoi(path: &Path, rotation_mode: RotationMode, auto_crop: bool, crop_border: u32) -> Result<ImageSequence> {
-    // Read entire file into memory (QOI decoder needs bytes)
-    let data = std::fs::read(path).map_err(|err| RimgError::ImageOpen {
-        path: path.to_path_buf(),
-        source: image::ImageError::IoError(err),
-    })?;
+/// The file extension of the video container `bytes` start with
+///
+/// HEIF and AVIF stills share the ISO-BMFF `ftyp` box with MP4 and MOV, so
+/// those brands are left to the image decoders.
+#[cfg(feature = "video")]
+fn video_container(bytes: &[u8]) -> Option<&'static str> {
+    const EBML: &[u8] = &[0x1A, 0x45, 0xDF, 0xA3];
+    if bytes.starts_with(EBML) {
+        // The DocType sits in the EBML header, a few dozen bytes in
+        let header = &bytes[..bytes.len().min(64)];
+        let webm = header.windows(4).any(|window| window == b"webm");
+        return Some(if webm { "webm" } else { "mkv" });
+    }
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"AVI " {
+        return Some("avi");
+    }
+    if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" && ContainerFormat::detect(bytes).is_none() {
+        return Some(if &bytes[8..12] == b"qt  " { "mov" } else { "mp4" });
+    }
+    None
+}
+
+#[cfg(feature = "video")]
+fn load_video_from_bytes(
+    bytes: &[u8],
+    hint: &Path,
+    container: Option<&str>,
+    options: &LoadOptions,
+) -> Result<Option<ImageSequence>> {
+    use std::io::Write;
+
+    // ffmpeg only opens files, so spill the buffer to a temporary one named
+    // after the container, since piped input has no useful extension
+    let ext = container
+        .or_else(|| hint.extension().and_then(|s| s.to_str()))
+        .unwrap_or("bin");
+    let (spill, mut file) = SpillFile::create(ext)?;
+    file.write_all(bytes)?;
+    drop(file);
+    let result = load_video_frames(&spill.0, first_frame_only(options), &options.limits);
+
+    Ok(result?.map(|mut sequence| {
+        sequence.path = hint.to_path_buf();
+        finish_frames(sequence, options)
+    }))
+}
+
+/// A private temporary file, removed however the load ends
+#[cfg(feature = "video")]
+struct SpillFile(PathBuf);
+
+#[cfg(feature = "video")]
+impl SpillFile {
+    /// Create a new file only the current user can read
+    ///
+    /// `create_new` refuses existing paths, symlinks included, so a file
+    /// planted in a shared temp directory can't redirect the write.
+    fn create(ext: &str) -> Result<(Self, File)> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        static SPILL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let mut open = std::fs::OpenOptions::new();
+        open.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            open.mode(0o600);
+        }
+        for _ in 0..16 {
+            let nonce = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.subsec_nanos());
+            let path = std::env::temp_dir().join(format!(
+                "showme-{}-{}-{:08x}.{}",
+                std::process::id(),
+                SPILL_COUNTER.fetch_add(1, Ordering::Relaxed),
+                nonce,
+                ext
+            ));
+            match open.open(&path) {
+                Ok(file) => return Ok((Self(path), file)),
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Err(RimgError::other("failed to create a temporary file for video data"))
+    }
+}
+
+#[cfg(feature = "video")]
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
 
-    let (header, data) = qoi::decode_to_vec(&data).map_err(|err| {
+#[cfg(feature = "qoi")]
+fn load_qoi(bytes: &[u8], path: &Path, options: &LoadOptions) -> Result<ImageSequence> {
+    if let Ok(header) = qoi::decode_header(bytes) {
+        options.limits.check_pixels(header.width, header.height, path)?;
+    }
+    let (header, data) = qoi::decode_to_vec(bytes).map_err(|err| {
         RimgError::other(format!("QOI decode error for '{}': {}", path.display(), err))
     })?;
 
@@ -256,37 +951,105 @@ fn load_qoi(path: &Path, rotation_mode: RotationMode, auto_crop: bool, crop_bord
     let rgba_image = RgbaImage::from_raw(header.width, header.height, rgba_data)
         .ok_or_else(|| RimgError::other("QOI: failed to create image from decoded data"))?;
 
-    let mut image = DynamicImage::ImageRgba8(rgba_image);
+    // QOI has no EXIF, so only cropping applies
+    let pixels = finish_still(DynamicImage::ImageRgba8(rgba_image), None, options);
+    Ok(ImageSequence {
+        path: path.to_path_buf(),
+        frames: vec![Frame::single(pixels)],
+        page: None,
+    })
+}
+
+/// Whether `path` has an extension that one of the compiled-in loaders handles
+pub fn is_supported_media(path: &Path) -> bool {
+    let Some(ext) = path.extension().and_then(|s| s.to_str()) else {
+        return false;
+    };
+    let ext = ext.to_ascii_lowercase();
 
-    // Apply rotation if needed (QOI doesn't have EXIF, but we check anyway)
-    if rotation_mode == RotationMode::Exif {
-        if let Some(orientation) = read_exif_orientation(path) {
-            image = apply_orientation(image, orientation);
+    #[cfg(feature = "video")]
+    {
+        if VideoLoader::is_video_candidate(path) {
+            return true;
         }
     }
-
-    // Apply cropping: first fixed border, then auto-crop
-    if crop_border > 0 {
-        image = autocrop::crop_border(image, crop_border);
+    #[cfg(feature = "archive")]
+    {
+        if archive::is_archive(path) {
+            return true;
+        }
+    }
+    #[cfg(feature = "qoi")]
+    {
+        if ext == "qoi" {
+            return true;
+        }
+    }
+    #[cfg(feature = "pdf")]
+    {
+        if ext == "pdf" {
+            return true;
+        }
+    }
+    #[cfg(feature = "svg")]
+    {
+        if ext == "svg" || ext == "svgz" {
+            return true;
+        }
     }
-    if auto_crop {
-        image = autocrop::auto_crop(image);
+    if raw::is_raw_path(path) {
+        return true;
+    }
+    #[cfg(feature = "heic")]
+    {
+        if ext == "heic" || ext == "heif" {
+            return true;
+        }
+    }
+    #[cfg(feature = "jxl")]
+    {
+        if ext == "jxl" {
+            return true;
+        }
     }
 
-    let pixels = image.to_rgba8();
-    Ok(ImageSequence {
-        path: path.to_path_buf(),
-        frames: vec![Frame::single(pixels)],
-    })
+    ImageFormat::from_extension(&ext).is_some_and(|format| format.reading_enabled())
 }
 
-/// Read EXIF orientation tag from an image file
-fn read_exif_orientation(path: &Path) -> Option<u32> {
+/// Read the EXIF capture date (`YYYY:MM:DD HH:MM:SS`) from an image file
+pub(crate) fn read_exif_datetime(path: &Path) -> Option<String> {
     let file = File::open(path).ok()?;
     let mut reader = BufReader::new(file);
+    let exif = exif::Reader::new().read_from_container(&mut reader).ok()?;
+
+    let field = exif
+        .get_field(exif::Tag::DateTimeOriginal, exif::In::PRIMARY)
+        .or_else(|| exif.get_field(exif::Tag::DateTime, exif::In::PRIMARY))?;
+
+    match field.value {
+        exif::Value::Ascii(ref parts) => parts
+            .first()
+            .map(|raw| String::from_utf8_lossy(raw).trim().to_string()),
+        _ => None,
+    }
+}
+
+/// EXIF orientation of an encoded image, if rotation is enabled and the tag is present
+fn exif_orientation(bytes: &[u8], options: &LoadOptions) -> Option<u32> {
+    if !options.rotation.follows_exif() {
+        return None;
+    }
+    read_exif_orientation(bytes)
+}
+
+/// Read the EXIF orientation tag from an encoded image
+fn read_exif_orientation(bytes: &[u8]) -> Option<u32> {
     let exif_reader = exif::Reader::new();
-    let exif = exif_reader.read_from_container(&mut reader).ok()?;
+    let exif = exif_reader.read_from_container(&mut Cursor::new(bytes)).ok()?;
+    orientation_tag(&exif)
+}
 
+fn orientation_tag(exif: &exif::Exif) -> Option<u32> {
     let orientation_field = exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)?;
 
     match orientation_field.value {
@@ -295,6 +1058,26 @@ fn read_exif_orientation(path: &Path) -> Option<u32> {
     }
 }
 
+/// Apply the requested rotation, flip and crop region, in that order
+fn apply_geometry(mut image: DynamicImage, options: &LoadOptions) -> DynamicImage {
+    image = match options.rotation.quarter_turns() {
+        1 => image.rotate90(),
+        2 => image.rotate180(),
+        3 => image.rotate270(),
+        _ => image,
+    };
+    image = match options.flip {
+        Some(Flip::Horizontal) => image.fliph(),
+        Some(Flip::Vertical) => image.flipv(),
+        Some(Flip::Both) => image.rotate180(),
+        None => image,
+    };
+    match &options.crop {
+        Some(region) => autocrop::crop_region(image, region),
+        None => image,
+    }
+}
+
 /// Apply EXIF orientation transformation to an image
 fn apply_orientation(image: DynamicImage, orientation: u32) -> DynamicImage {
     use image::imageops::{flip_horizontal, flip_vertical, rotate180, rotate270, rotate90};
@@ -320,114 +1103,395 @@ fn apply_orientation(image: DynamicImage, orientation: u32) -> DynamicImage {
     }
 }
 
+/// Width PDF pages are rendered at when no target or raster size is known
 #[cfg(feature = "pdf")]
-fn load_pdf(path: &Path, rotation_mode: RotationMode, auto_crop: bool, crop_border: u32) -> Result<ImageSequence> {
-    use pdfium_render::prelude::*;
+const PDF_DEFAULT_WIDTH: u32 = 2000;
+/// Widest bitmap a PDF page is rendered to, whatever the target
+#[cfg(feature = "pdf")]
+const PDF_MAX_WIDTH: u32 = 4000;
 
-    // Initialize Pdfium library
-    let pdfium = Pdfium::new(
-        Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path("./"))
-            .or_else(|_| Pdfium::bind_to_system_library())
-            .map_err(|err| RimgError::other(format!("Failed to initialize PDF library: {}", err)))?
-    );
+/// Every page of a PDF as one frame each
+///
+/// The renderer shows pages as separate images through [`expand_input`];
+/// this keeps `load_image_from_bytes` able to decode a PDF on its own.
+#[cfg(feature = "pdf")]
+fn load_pdf(bytes: &[u8], path: &Path, options: &LoadOptions) -> Result<ImageSequence> {
+    let document = open_pdf(bytes.to_vec(), path)?;
+    let page_count = usize::from(document.pages().len());
+    if page_count == 0 {
+        return Err(RimgError::other(format!("PDF '{}' has no pages", path.display())));
+    }
+    // A thumbnail only needs the first page
+    let page_count = if options.target_size.is_some() { 1 } else { page_count };
+    options.limits.check_frames(page_count, path)?;
+
+    let started = Instant::now();
+    let mut frames = Vec::with_capacity(page_count);
+    for index in 0..page_count {
+        frames.push(Frame::single(render_pdf_page(&document, index, path, options)?));
+        options.limits.check_time(started, path)?;
+    }
 
-    // Load the PDF document
-    let document = pdfium
-        .load_pdf_from_file(path, None)
-        .map_err(|err| RimgError::other(format!("Failed to load PDF '{}': {}", path.display(), err)))?;
+    Ok(ImageSequence {
+        path: path.to_path_buf(),
+        frames,
+        page: None,
+    })
+}
 
-    let page_count = document.pages().len();
-    if page_count == 0 {
+/// Count the pages of a PDF and defer rendering the selected ones
+#[cfg(feature = "pdf")]
+fn document_pages(
+    data: Vec<u8>,
+    path: &Path,
+    options: &LoadOptions,
+    pages: Option<&PageSelection>,
+) -> Result<Vec<PendingImage>> {
+    let document = open_pdf(data, path)?;
+    let count = usize::from(document.pages().len());
+    if count == 0 {
         return Err(RimgError::other(format!("PDF '{}' has no pages", path.display())));
     }
 
-    let mut frames = Vec::with_capacity(page_count as usize);
+    let indices = match pages {
+        Some(selection) => selection.indices(count),
+        None if options.target_size.is_some() => vec![0],
+        None => (0..count).collect(),
+    };
+    if indices.is_empty() {
+        return Err(RimgError::other(format!(
+            "none of the selected pages exist in '{}', which has {} pages",
+            path.display(),
+            count
+        )));
+    }
+    options.limits.check_frames(indices.len(), path)?;
 
-    // Render each page
-    for page_index in 0..page_count {
-        let page = document
-            .pages()
-            .get(page_index)
-            .map_err(|err| RimgError::other(format!("Failed to get page {} from PDF: {}", page_index, err)))?;
+    let document = Arc::new(document);
+    Ok(indices
+        .into_iter()
+        .map(|index| {
+            PendingImage::Page(DocumentPage {
+                path: path.to_path_buf(),
+                document: Arc::clone(&document),
+                index,
+                count,
+            })
+        })
+        .collect())
+}
 
-        // Render at 150 DPI for good quality
-        let render_config = PdfRenderConfig::new()
-            .set_target_width(2000)
-            .set_maximum_width(4000);
+/// Whether the file at `path` starts like a PDF, whatever its name
+#[cfg(feature = "pdf")]
+fn has_pdf_header(path: &Path) -> bool {
+    let mut header = [0u8; 5];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok_and(|()| &header == b"%PDF-")
+}
 
-        let bitmap = page
-            .render_with_config(&render_config)
-            .map_err(|err| RimgError::other(format!("Failed to render PDF page {}: {}", page_index, err)))?;
+/// The Pdfium library, bound on first use and shared from then on
+///
+/// Binding loads the shared library, so it happens once per process; a
+/// failure is remembered too, rather than retried for every page.
+#[cfg(feature = "pdf")]
+fn pdfium() -> Result<&'static pdfium_render::prelude::Pdfium> {
+    use pdfium_render::prelude::*;
 
-        // Convert bitmap to RgbaImage
-        let width = bitmap.width() as u32;
-        let height = bitmap.height() as u32;
+    static PDFIUM: std::sync::OnceLock<std::result::Result<Pdfium, String>> = std::sync::OnceLock::new();
+    PDFIUM
+        .get_or_init(|| {
+            // The library next to the executable, or else the system one
+            Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path("./"))
+                .or_else(|_| Pdfium::bind_to_system_library())
+                .map(Pdfium::new)
+                .map_err(|err| err.to_string())
+        })
+        .as_ref()
+        .map_err(|err| RimgError::other(format!("Failed to initialize PDF library: {}", err)))
+}
 
-        let rgba_data: Vec<u8> = bitmap
-            .as_raw_bytes()
-            .chunks(4)
-            .flat_map(|pixel| {
-                // Pdfium returns BGRA, convert to RGBA
-                [pixel[2], pixel[1], pixel[0], pixel[3]]
-            })
-            .collect();
+/// Parse a PDF, which keeps `bytes` alive for as long as it is open
+#[cfg(feature = "pdf")]
+fn open_pdf(bytes: Vec<u8>, path: &Path) -> Result<pdfium_render::prelude::PdfDocument<'static>> {
+    pdfium()?
+        .load_pdf_from_byte_vec(bytes, None)
+        .map_err(|err| RimgError::other(format!("Failed to load PDF '{}': {}", path.display(), err)))
+}
 
-        let rgba_image = RgbaImage::from_raw(width, height, rgba_data)
-            .ok_or_else(|| RimgError::other("PDF: failed to create image from rendered data"))?;
+/// Render page `index` to fit `options.target_size`, else `options.raster_size`
+#[cfg(feature = "pdf")]
+fn render_pdf_page(
+    document: &pdfium_render::prelude::PdfDocument<'_>,
+    index: usize,
+    path: &Path,
+    options: &LoadOptions,
+) -> Result<RgbaImage> {
+    use pdfium_render::prelude::*;
 
-        let mut image = DynamicImage::ImageRgba8(rgba_image);
+    let page = u16::try_from(index)
+        .ok()
+        .and_then(|index| document.pages().get(index).ok())
+        .ok_or_else(|| RimgError::other(format!("Failed to get page {} from PDF", index + 1)))?;
+
+    // Fit the page into the box, keeping its aspect ratio
+    let (box_width, box_height) = options
+        .target_size
+        .or(options.raster_size)
+        .unwrap_or((PDF_DEFAULT_WIDTH, u32::MAX));
+    let (page_width, page_height) = (page.width().value.max(1.0), page.height().value.max(1.0));
+    let scale = (box_width as f32 / page_width).min(box_height as f32 / page_height);
+    let target_width = ((page_width * scale).round() as u32).clamp(1, PDF_MAX_WIDTH);
+    let rendered_height = (target_width as f32 * page_height / page_width).ceil() as u32;
+    options.limits.check_pixels(target_width, rendered_height, path)?;
+
+    let render_config = PdfRenderConfig::new()
+        .set_target_width(target_width as i32)
+        .set_maximum_width(PDF_MAX_WIDTH as i32);
+    let bitmap = page
+        .render_with_config(&render_config)
+        .map_err(|err| RimgError::other(format!("Failed to render PDF page {}: {}", index + 1, err)))?;
+
+    // Convert bitmap to RgbaImage
+    let width = bitmap.width() as u32;
+    let height = bitmap.height() as u32;
+
+    let rgba_data: Vec<u8> = bitmap
+        .as_raw_bytes()
+        .chunks(4)
+        .flat_map(|pixel| {
+            // Pdfium returns BGRA, convert to RGBA
+            [pixel[2], pixel[1], pixel[0], pixel[3]]
+        })
+        .collect();
 
-        // Apply rotation if needed (PDFs don't typically have EXIF, but check anyway)
-        if rotation_mode == RotationMode::Exif {
-            if let Some(orientation) = read_exif_orientation(path) {
-                image = apply_orientation(image, orientation);
-            }
+    let rgba_image = RgbaImage::from_raw(width, height, rgba_data)
+        .ok_or_else(|| RimgError::other("PDF: failed to create image from rendered data"))?;
+
+    // PDF pages carry no EXIF, so only cropping applies
+    Ok(finish_still(DynamicImage::ImageRgba8(rgba_image), None, options))
+}
+
+/// Show the largest JPEG preview embedded in a camera RAW file
+fn load_raw(bytes: &[u8], path: &Path, options: &LoadOptions) -> Result<ImageSequence> {
+    let preview = raw::extract_preview(bytes, path)?;
+    let image = decode_still(preview.jpeg, Some(ImageFormat::Jpeg), path, options)?;
+
+    // The RAW's IFD0 orientation applies; the preview's own EXIF is ignored
+    let orientation = preview.orientation.filter(|_| options.rotation.follows_exif());
+    Ok(ImageSequence {
+        path: path.to_path_buf(),
+        frames: vec![Frame::single(finish_still(image, orientation, options))],
+        page: None,
+    })
+}
+
+/// Formats decoded outside the `image` crate's reader, recognised by magic bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ContainerFormat {
+    Avif,
+    Heic,
+    Jxl,
+}
+
+impl ContainerFormat {
+    fn detect(bytes: &[u8]) -> Option<Self> {
+        const JXL_CODESTREAM: &[u8] = &[0xFF, 0x0A];
+        const JXL_CONTAINER: &[u8] = b"\0\0\0\x0cJXL \r\n\x87\n";
+        if bytes.starts_with(JXL_CODESTREAM) || bytes.starts_with(JXL_CONTAINER) {
+            return Some(Self::Jxl);
         }
 
-        // Apply cropping: first fixed border, then auto-crop
-        if crop_border > 0 {
-            image = autocrop::crop_border(image, crop_border);
+        // ISOBMFF `ftyp` box: major brand, minor version, then compatible brands
+        if bytes.len() < 16 || &bytes[4..8] != b"ftyp" {
+            return None;
         }
-        if auto_crop {
-            image = autocrop::auto_crop(image);
+        let size = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+        let end = size.clamp(16, bytes.len());
+        let brands = std::iter::once(&bytes[8..12]).chain(bytes[16..end].chunks_exact(4));
+
+        let mut heif = false;
+        for brand in brands {
+            match brand {
+                b"avif" | b"avis" => return Some(Self::Avif),
+                b"heic" | b"heix" | b"heim" | b"heis" | b"hevc" | b"hevx" | b"mif1" | b"msf1" => heif = true,
+                _ => {}
+            }
+        }
+        heif.then_some(Self::Heic)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Avif => "an AVIF image",
+            Self::Heic => "a HEIC image",
+            Self::Jxl => "a JPEG XL image",
         }
+    }
 
-        frames.push(Frame::single(image.to_rgba8()));
+    fn feature(self) -> &'static str {
+        match self {
+            Self::Avif => "avif",
+            Self::Heic => "heic",
+            Self::Jxl => "jxl",
+        }
     }
+}
 
+#[cfg(feature = "avif")]
+fn load_avif(bytes: &[u8], path: &Path, options: &LoadOptions) -> Result<ImageSequence> {
+    let image = decode_still(bytes, Some(ImageFormat::Avif), path, options)?;
+
+    // The EXIF block lives in the HEIF `meta` box, which the EXIF reader understands
+    let orientation = exif_orientation(bytes, options);
     Ok(ImageSequence {
         path: path.to_path_buf(),
-        frames,
+        frames: vec![Frame::single(finish_still(image, orientation, options))],
+        page: None,
     })
 }
 
-#[cfg(feature = "svg")]
-fn load_svg(path: &Path, rotation_mode: RotationMode, auto_crop: bool, crop_border: u32) -> Result<ImageSequence> {
-    use resvg::usvg;
+#[cfg(feature = "heic")]
+fn load_heic(bytes: &[u8], path: &Path, options: &LoadOptions) -> Result<ImageSequence> {
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    let invalid = |err: libheif_rs::HeifError| {
+        RimgError::other(format!("HEIC: failed to decode '{}': {}", path.display(), err))
+    };
+    let context = HeifContext::read_from_bytes(bytes).map_err(invalid)?;
+    let handle = context.primary_image_handle().map_err(invalid)?;
+    options.limits.check_pixels(handle.width(), handle.height(), path)?;
+
+    // libheif applies the container's clean aperture, rotation and mirroring
+    // (`clap`, `irot`, `imir`), which HEIF makes authoritative over EXIF
+    let decoded = LibHeif::new()
+        .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgba), None)
+        .map_err(invalid)?;
+
+    let planes = decoded.planes();
+    let plane = planes
+        .interleaved
+        .ok_or_else(|| RimgError::other(format!("HEIC: '{}' decoded without RGBA data", path.display())))?;
+    let row_bytes = plane.width as usize * 4;
+    let mut pixels = Vec::with_capacity(row_bytes * plane.height as usize);
+    for row in plane.data.chunks(plane.stride).take(plane.height as usize) {
+        pixels.extend_from_slice(&row[..row_bytes]);
+    }
+    let image = RgbaImage::from_raw(plane.width, plane.height, pixels)
+        .ok_or_else(|| RimgError::other(format!("HEIC: '{}' has an inconsistent pixel buffer", path.display())))?;
 
-    // Read SVG file
-    let svg_data = std::fs::read(path).map_err(|err| RimgError::ImageOpen {
+    // The EXIF orientation only describes the transforms already applied above
+    Ok(ImageSequence {
         path: path.to_path_buf(),
-        source: image::ImageError::IoError(err),
-    })?;
+        frames: vec![Frame::single(finish_still(DynamicImage::ImageRgba8(image), None, options))],
+        page: None,
+    })
+}
 
-    // Parse SVG
-    let options = usvg::Options::default();
-    let tree = usvg::Tree::from_data(&svg_data, &options)
-        .map_err(|err| RimgError::other(format!("Failed to parse SVG '{}': {}", path.display(), err)))?;
+#[cfg(feature = "jxl")]
+fn load_jxl(bytes: &[u8], path: &Path, options: &LoadOptions) -> Result<ImageSequence> {
+    use jxl_oxide::JxlImage;
 
-    // Get SVG size
-    let size = tree.size();
+    let invalid = |err: &dyn std::fmt::Display| {
+        RimgError::other(format!("JPEG XL: failed to decode '{}': {}", path.display(), err))
+    };
+    let jxl = JxlImage::builder().read(Cursor::new(bytes)).map_err(|err| invalid(&err))?;
+    options.limits.check_pixels(jxl.width(), jxl.height(), path)?;
+    let render = jxl.render_frame(0).map_err(|err| invalid(&err))?;
+    let buffer = render.image_all_channels();
+
+    let to_u8 = |sample: f32| (sample.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let mut image = RgbaImage::new(buffer.width() as u32, buffer.height() as u32);
+    for (pixel, samples) in image.pixels_mut().zip(buffer.buf().chunks_exact(buffer.channels())) {
+        let [r, g, b, a] = match *samples {
+            [gray] => [gray, gray, gray, 1.0],
+            [gray, alpha] => [gray, gray, gray, alpha],
+            [r, g, b] => [r, g, b, 1.0],
+            [r, g, b, alpha, ..] => [r, g, b, alpha],
+            [] => [0.0, 0.0, 0.0, 0.0],
+        };
+        *pixel = image::Rgba([to_u8(r), to_u8(g), to_u8(b), to_u8(a)]);
+    }
 
-    // Render at a reasonable resolution (scale up small SVGs)
-    let scale = if size.width() < 800.0 {
-        800.0 / size.width()
+    // The decoder already honours the codestream orientation; the Exif box only
+    // matters when that one is the identity
+    let orientation = if jxl.image_header().metadata.orientation == 1 {
+        jxl_exif_orientation(bytes, options)
     } else {
-        1.0
+        None
     };
+    Ok(ImageSequence {
+        path: path.to_path_buf(),
+        frames: vec![Frame::single(finish_still(DynamicImage::ImageRgba8(image), orientation, options))],
+        page: None,
+    })
+}
+
+/// EXIF orientation from the `Exif` box of a JPEG XL container
+#[cfg(feature = "jxl")]
+fn jxl_exif_orientation(bytes: &[u8], options: &LoadOptions) -> Option<u32> {
+    if !options.rotation.follows_exif() {
+        return None;
+    }
+    let tiff = jxl_exif_payload(bytes)?;
+    let exif = exif::Reader::new().read_raw(tiff.to_vec()).ok()?;
+    orientation_tag(&exif)
+}
+
+/// TIFF data of the `Exif` box, skipping its 4-byte header offset
+#[cfg(feature = "jxl")]
+fn jxl_exif_payload(bytes: &[u8]) -> Option<&[u8]> {
+    let mut rest = bytes;
+    while rest.len() >= 8 {
+        let size = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let (header, size) = match size {
+            // 64-bit size follows the box type
+            1 if rest.len() >= 16 => (16, u64::from_be_bytes(rest[8..16].try_into().ok()?) as usize),
+            // Box extends to the end of the file
+            0 => (8, rest.len()),
+            size => (8, size),
+        };
+        if size < header || size > rest.len() {
+            return None;
+        }
+        if &rest[4..8] == b"Exif" {
+            let body = &rest[header..size];
+            let offset = u32::from_be_bytes(body.get(..4)?.try_into().ok()?) as usize;
+            return body.get(4 + offset..);
+        }
+        rest = &rest[size..];
+    }
+    None
+}
+
+/// Whether extension-less data looks like an SVG document
+#[cfg(feature = "svg")]
+fn looks_like_svg(bytes: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]);
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    head.starts_with("<svg") || (head.starts_with("<?xml") && head.contains("<svg"))
+}
+
+#[cfg(feature = "svg")]
+fn load_svg(bytes: &[u8], path: &Path, options: &LoadOptions) -> Result<ImageSequence> {
+    use resvg::usvg;
+
+    // Parse SVG (gzip-compressed SVGZ data is detected automatically);
+    // text is turned into paths here, so the fonts have to be known already
+    let fontdb = svg_fonts();
+    let usvg_options = usvg::Options {
+        font_family: fontdb.family_name(&usvg::fontdb::Family::Serif).to_owned(),
+        fontdb,
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_data(bytes, &usvg_options)
+        .map_err(|err| RimgError::other(format!("Failed to parse SVG '{}': {}", path.display(), err)))?;
 
-    let width = (size.width() * scale) as u32;
-    let height = (size.height() * scale) as u32;
+    let size = tree.size();
+    let scale = svg_scale(size.width(), size.height(), options.target_size.or(options.raster_size));
+    let width = ((size.width() * scale).round() as u32).max(1);
+    let height = ((size.height() * scale).round() as u32).max(1);
+    options.limits.check_pixels(width, height, path)?;
 
     // Create pixmap for rendering
     let mut pixmap = resvg::tiny_skia::Pixmap::new(width, height)
@@ -443,25 +1507,365 @@ fn load_svg(path: &Path, rotation_mode: RotationMode, auto_crop: bool, crop_bord
     let rgba_image = RgbaImage::from_raw(width, height, rgba_data)
         .ok_or_else(|| RimgError::other("SVG: failed to create image from rendered data"))?;
 
-    let mut image = DynamicImage::ImageRgba8(rgba_image);
+    // SVGs carry no EXIF, so only cropping applies
+    Ok(ImageSequence {
+        path: path.to_path_buf(),
+        frames: vec![Frame::single(finish_still(DynamicImage::ImageRgba8(rgba_image), None, options))],
+        page: None,
+    })
+}
+
+/// Width small SVGs are scaled up to when there is no size to fit
+#[cfg(feature = "svg")]
+const SVG_DEFAULT_WIDTH: f32 = 800.0;
+
+/// Scale that fits a `width`×`height` drawing into `target`, up or down
+///
+/// Without a target (or with both sides unbounded) small drawings are
+/// enlarged to [`SVG_DEFAULT_WIDTH`] and large ones keep their size.
+#[cfg(feature = "svg")]
+fn svg_scale(width: f32, height: f32, target: Option<(u32, u32)>) -> f32 {
+    let fit = |bound: u32, length: f32| (bound != u32::MAX).then(|| bound as f32 / length.max(1.0));
+    match target.map(|(bound_width, bound_height)| (fit(bound_width, width), fit(bound_height, height))) {
+        Some((Some(x), Some(y))) => x.min(y),
+        Some((Some(scale), None) | (None, Some(scale))) => scale,
+        _ => (SVG_DEFAULT_WIDTH / width.max(1.0)).max(1.0),
+    }
+}
 
-    // Apply rotation if needed (SVGs don't have EXIF, but check anyway)
-    if rotation_mode == RotationMode::Exif {
-        if let Some(orientation) = read_exif_orientation(path) {
-            image = apply_orientation(image, orientation);
+/// System fonts for SVG text, loaded once and shared by every SVG
+///
+/// fontdb maps the generic families to Windows fonts, so each one is
+/// pointed at the first common font actually installed.
+#[cfg(feature = "svg")]
+fn svg_fonts() -> Arc<resvg::usvg::fontdb::Database> {
+    use resvg::usvg::fontdb::Database;
+
+    static FONTS: std::sync::OnceLock<Arc<Database>> = std::sync::OnceLock::new();
+    Arc::clone(FONTS.get_or_init(|| {
+        let mut fonts = Database::new();
+        fonts.load_system_fonts();
+        let installed = |fonts: &Database, candidates: &[&'static str]| {
+            candidates.iter().copied().find(|candidate| {
+                fonts.faces().any(|face| face.families.iter().any(|(name, _)| name == candidate))
+            })
+        };
+        if let Some(family) = installed(&fonts, &["Arial", "Helvetica", "Liberation Sans", "DejaVu Sans", "Noto Sans"]) {
+            fonts.set_sans_serif_family(family);
+        }
+        if let Some(family) = installed(&fonts, &["Times New Roman", "Times", "Liberation Serif", "DejaVu Serif", "Noto Serif"]) {
+            fonts.set_serif_family(family);
         }
+        if let Some(family) = installed(&fonts, &["Courier New", "Courier", "Liberation Mono", "DejaVu Sans Mono", "Noto Sans Mono"]) {
+            fonts.set_monospace_family(family);
+        }
+        Arc::new(fonts)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::jpeg::JpegEncoder;
+
+    /// A 4x2 JPEG carrying an EXIF orientation tag
+    fn jpeg_with_orientation(orientation: u8) -> Vec<u8> {
+        let mut jpeg = Vec::new();
+        let pixels = RgbaImage::from_pixel(4, 2, image::Rgba([200, 10, 10, 255]));
+        JpegEncoder::new(&mut jpeg)
+            .encode_image(&DynamicImage::ImageRgba8(pixels).to_rgb8())
+            .expect("encode jpeg");
+
+        // Big-endian TIFF header with a single IFD entry: Orientation (SHORT)
+        let mut app1 = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\x01\x01\x12\0\x03\0\0\0\x01\0".to_vec();
+        app1.extend_from_slice(&[orientation, 0, 0, 0, 0, 0, 0]);
+        let length = (app1.len() + 2) as u16;
+
+        let mut out = jpeg[..2].to_vec();
+        out.extend_from_slice(&[0xFF, 0xE1]);
+        out.extend_from_slice(&length.to_be_bytes());
+        out.extend_from_slice(&app1);
+        out.extend_from_slice(&jpeg[2..]);
+        out
     }
 
-    // Apply cropping: first fixed border, then auto-crop
-    if crop_border > 0 {
-        image = autocrop::crop_border(image, crop_border);
+    #[test]
+    fn applies_exif_orientation_from_buffer() {
+        let bytes = jpeg_with_orientation(6);
+        let sequence =
+            load_image_from_bytes(&bytes, Path::new("<stdin>"), &LoadOptions::default()).expect("decode");
+        let frame = sequence.first_frame().expect("frame");
+        assert_eq!(frame.pixels.dimensions(), (2, 4));
+        assert_eq!(sequence.path, Path::new("<stdin>"));
+
+        let off = LoadOptions::new(RotationMode::Off, false, 0);
+        let sequence = load_image_from_bytes(&bytes, Path::new("photo.jpg"), &off).expect("decode");
+        assert_eq!(sequence.first_frame().expect("frame").pixels.dimensions(), (4, 2));
+    }
+
+    #[test]
+    fn decodes_apng_frames_from_stdin() {
+        let mut apng = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut apng, 2, 2);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_animated(2, 0).expect("animated");
+            let mut writer = encoder.write_header().expect("header");
+            for (color, centis) in [([255, 0, 0, 255], 10), ([0, 0, 255, 255], 25)] {
+                writer.set_frame_delay(centis, 100).expect("delay");
+                writer.write_image_data(&color.repeat(4)).expect("frame");
+            }
+            writer.finish().expect("finish");
+        }
+
+        let sequence =
+            load_image_from_bytes(&apng, Path::new("<stdin>"), &LoadOptions::default()).expect("decode");
+        assert_eq!(sequence.frames.len(), 2);
+        assert_eq!(sequence.frames[0].delay, Duration::from_millis(100));
+        assert_eq!(sequence.frames[1].delay, Duration::from_millis(250));
+        assert_eq!(*sequence.frames[1].pixels.get_pixel(1, 1), image::Rgba([0, 0, 255, 255]));
     }
-    if auto_crop {
-        image = autocrop::auto_crop(image);
+
+    #[test]
+    fn detects_modern_formats_by_magic_bytes() {
+        let ftyp = |major: &[u8], compatible: &[u8]| {
+            let mut bytes = ((16 + compatible.len()) as u32).to_be_bytes().to_vec();
+            bytes.extend_from_slice(b"ftyp");
+            bytes.extend_from_slice(major);
+            bytes.extend_from_slice(&[0, 0, 0, 0]);
+            bytes.extend_from_slice(compatible);
+            bytes
+        };
+        assert_eq!(ContainerFormat::detect(&ftyp(b"avif", b"mif1miaf")), Some(ContainerFormat::Avif));
+        assert_eq!(ContainerFormat::detect(&ftyp(b"mif1", b"avifmiaf")), Some(ContainerFormat::Avif));
+        assert_eq!(ContainerFormat::detect(&ftyp(b"heic", b"mif1heic")), Some(ContainerFormat::Heic));
+        assert_eq!(ContainerFormat::detect(&ftyp(b"isom", b"mp41")), None);
+        assert_eq!(ContainerFormat::detect(&[0xFF, 0x0A, 0x00]), Some(ContainerFormat::Jxl));
+        assert_eq!(ContainerFormat::detect(b"\0\0\0\x0cJXL \r\n\x87\n\0\0"), Some(ContainerFormat::Jxl));
+        assert_eq!(ContainerFormat::detect(b"\x89PNG\r\n\x1a\n"), None);
     }
 
-    Ok(ImageSequence {
-        path: path.to_path_buf(),
-        frames: vec![Frame::single(image.to_rgba8())],
-    })
+    #[cfg(feature = "video")]
+    #[test]
+    fn sniffs_video_containers() {
+        let ftyp = |major: &[u8], compatible: &[u8]| {
+            let mut bytes = ((16 + compatible.len()) as u32).to_be_bytes().to_vec();
+            bytes.extend_from_slice(b"ftyp");
+            bytes.extend_from_slice(major);
+            bytes.extend_from_slice(&[0, 0, 0, 0]);
+            bytes.extend_from_slice(compatible);
+            bytes
+        };
+        assert_eq!(video_container(&ftyp(b"isom", b"isomavc1mp41")), Some("mp4"));
+        assert_eq!(video_container(&ftyp(b"qt  ", b"qt  ")), Some("mov"));
+        assert_eq!(video_container(&ftyp(b"heic", b"mif1heic")), None);
+        assert_eq!(video_container(b"\x1a\x45\xdf\xa3\x9f\x42\x86\x81\x01\x42\x82\x84webm"), Some("webm"));
+        assert_eq!(video_container(b"\x1a\x45\xdf\xa3\xa3\x42\x82\x88matroska"), Some("mkv"));
+        assert_eq!(video_container(b"RIFF\0\0\0\0AVI LIST"), Some("avi"));
+        assert_eq!(video_container(b"RIFF\0\0\0\0WEBPVP8 "), None);
+    }
+
+    /// A 64x48 red JPEG whose EXIF carries a 16x12 blue thumbnail
+    fn jpeg_with_thumbnail() -> Vec<u8> {
+        let encode = |width, height, color| {
+            let mut out = Vec::new();
+            JpegEncoder::new(&mut out)
+                .encode_image(&image::RgbImage::from_pixel(width, height, image::Rgb(color)))
+                .expect("encode jpeg");
+            out
+        };
+        let (photo, thumbnail) = (encode(64, 48, [220, 0, 0]), encode(16, 12, [0, 0, 220]));
+
+        // Big-endian TIFF: IFD0 with no entries, IFD1 pointing at the thumbnail
+        let mut tiff = b"MM\0*\0\0\0\x08\0\0\0\0\0\x0e\0\x02".to_vec();
+        tiff.extend_from_slice(&[0x02, 0x01, 0, 4, 0, 0, 0, 1, 0, 0, 0, 44]);
+        tiff.extend_from_slice(&[0x02, 0x02, 0, 4, 0, 0, 0, 1]);
+        tiff.extend_from_slice(&(thumbnail.len() as u32).to_be_bytes());
+        tiff.extend_from_slice(&[0, 0, 0, 0]);
+        tiff.extend_from_slice(&thumbnail);
+        let mut app1 = b"Exif\0\0".to_vec();
+        app1.extend_from_slice(&tiff);
+
+        let mut out = photo[..2].to_vec();
+        out.extend_from_slice(&[0xFF, 0xE1]);
+        out.extend_from_slice(&((app1.len() + 2) as u16).to_be_bytes());
+        out.extend_from_slice(&app1);
+        out.extend_from_slice(&photo[2..]);
+        out
+    }
+
+    #[test]
+    fn uses_exif_thumbnail_when_it_covers_the_target() {
+        let bytes = jpeg_with_thumbnail();
+        let hint = Path::new("photo.jpg");
+
+        let small = LoadOptions::default().with_target_size(8, 8);
+        let sequence = load_image_from_bytes(&bytes, hint, &small).expect("decode");
+        let frame = sequence.first_frame().expect("frame");
+        assert!(frame.pixels.width() <= 8 && frame.pixels.height() <= 8);
+        assert!(frame.pixels.get_pixel(0, 0)[2] > 150);
+
+        let large = LoadOptions::default().with_target_size(40, 40);
+        let sequence = load_image_from_bytes(&bytes, hint, &large).expect("decode");
+        let frame = sequence.first_frame().expect("frame");
+        assert_eq!(frame.pixels.dimensions(), (40, 30));
+        assert!(frame.pixels.get_pixel(0, 0)[0] > 150);
+
+        let full = load_image_from_bytes(&bytes, hint, &LoadOptions::default()).expect("decode");
+        assert_eq!(full.first_frame().expect("frame").pixels.dimensions(), (64, 48));
+    }
+
+    #[test]
+    fn refuses_inputs_over_the_decode_limits() {
+        let mut png = Vec::new();
+        image::GrayImage::new(2000, 1000)
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .expect("encode png");
+        let limited = |limits| LoadOptions::default().with_limits(limits);
+
+        let pixels = DecodeLimits {
+            max_pixels: Some(1_000_000),
+            ..DecodeLimits::default()
+        };
+        match load_image_from_bytes(&png, Path::new("bomb.png"), &limited(pixels)) {
+            Err(RimgError::LimitExceeded { path, limit }) => {
+                assert_eq!(path, Path::new("bomb.png"));
+                assert_eq!(limit, Limit::Pixels(1_000_000));
+            }
+            other => panic!("expected a pixel limit error, got {other:?}"),
+        }
+
+        let bytes = DecodeLimits {
+            max_bytes: Some(png.len() as u64 - 1),
+            ..DecodeLimits::default()
+        };
+        let err = load_image_from_bytes(&png, Path::new("bomb.png"), &limited(bytes)).unwrap_err();
+        assert!(matches!(err, RimgError::LimitExceeded { limit: Limit::Bytes(_), .. }));
+
+        let mut gif = Vec::new();
+        {
+            let mut encoder = image::codecs::gif::GifEncoder::new(&mut gif);
+            let frames = (0..3).map(|_| image::Frame::new(RgbaImage::new(2, 2)));
+            encoder.encode_frames(frames).expect("encode gif");
+        }
+        let frames = DecodeLimits {
+            max_frames: Some(2),
+            ..DecodeLimits::default()
+        };
+        let err = load_image_from_bytes(&gif, Path::new("anim.gif"), &limited(frames)).unwrap_err();
+        assert!(matches!(err, RimgError::LimitExceeded { limit: Limit::Frames(2), .. }));
+        let sequence = load_image_from_bytes(&gif, Path::new("anim.gif"), &LoadOptions::default()).expect("decode");
+        assert_eq!(sequence.frames.len(), 3);
+    }
+
+    #[test]
+    fn converts_icc_tagged_images_to_srgb() {
+        use image::ImageEncoder;
+        use image::codecs::png::PngEncoder;
+
+        let mut png = Vec::new();
+        let mut encoder = PngEncoder::new(&mut png);
+        let p3 = moxcms::ColorProfile::new_display_p3().encode().expect("encode profile");
+        encoder.set_icc_profile(p3).expect("icc");
+        encoder
+            .write_image(&[0, 200, 0], 1, 1, image::ExtendedColorType::Rgb8)
+            .expect("encode png");
+
+        let pixel = |options: &LoadOptions| {
+            let sequence = load_image_from_bytes(&png, Path::new("p3.png"), options).expect("decode");
+            sequence.frames[0].pixels.get_pixel(0, 0).0
+        };
+        // P3 green is more saturated than sRGB can show, so red goes negative and clips
+        let managed = pixel(&LoadOptions::default());
+        assert_eq!(managed[0], 0);
+        assert!(managed[1] > 200, "{managed:?}");
+        assert_eq!(pixel(&LoadOptions::default().with_color_management(false)), [0, 200, 0, 255]);
+    }
+
+    #[test]
+    fn crops_in_memory_images() {
+        let mut png = Vec::new();
+        RgbaImage::from_pixel(10, 10, image::Rgba([0, 0, 0, 255]))
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .expect("encode png");
+
+        let options = LoadOptions::new(RotationMode::Exif, false, 2);
+        let sequence = load_image_from_bytes(&png, Path::new("square"), &options).expect("decode");
+        assert_eq!(sequence.first_frame().expect("frame").pixels.dimensions(), (6, 6));
+    }
+
+    #[test]
+    fn rotates_flips_and_crops_every_gif_frame() {
+        use image::codecs::gif::GifEncoder;
+
+        // Red on the left, blue on the right
+        let split = RgbaImage::from_fn(4, 2, |x, _| {
+            if x < 2 { image::Rgba([255, 0, 0, 255]) } else { image::Rgba([0, 0, 255, 255]) }
+        });
+        let mut gif = Vec::new();
+        GifEncoder::new(&mut gif)
+            .encode_frames((0..2).map(|_| image::Frame::new(split.clone())))
+            .expect("encode gif");
+
+        // A quarter turn puts red on top, the flip puts blue there, the crop keeps the top half
+        let options = LoadOptions::new(RotationMode::Clockwise90, false, 0)
+            .with_flip(Some(Flip::Vertical))
+            .with_crop(Some("100%x50%".parse().expect("region")));
+        let sequence = load_image_from_bytes(&gif, Path::new("anim.gif"), &options).expect("decode");
+        assert_eq!(sequence.frames.len(), 2);
+        for frame in &sequence.frames {
+            assert_eq!(frame.pixels.dimensions(), (2, 2));
+            assert!(frame.pixels.pixels().all(|pixel| pixel[2] > 200 && pixel[0] < 50));
+        }
+    }
+
+    #[test]
+    fn auto_crops_animations_to_the_union_of_their_frames() {
+        use image::codecs::gif::GifEncoder;
+
+        // A white dot moving across a black canvas
+        let frames = [2, 9].map(|x| {
+            let mut canvas = RgbaImage::from_pixel(16, 8, image::Rgba([0, 0, 0, 255]));
+            canvas.put_pixel(x, 4, image::Rgba([255, 255, 255, 255]));
+            image::Frame::new(canvas)
+        });
+        let mut gif = Vec::new();
+        GifEncoder::new(&mut gif).encode_frames(frames).expect("encode gif");
+
+        let options = LoadOptions::new(RotationMode::Exif, true, 0);
+        let sequence = load_image_from_bytes(&gif, Path::new("dot.gif"), &options).expect("decode");
+        let sizes: Vec<_> = sequence.frames.iter().map(|frame| frame.pixels.dimensions()).collect();
+        assert_eq!(sizes, [(8, 1), (8, 1)]);
+        assert_eq!(sequence.frames[1].pixels.get_pixel(7, 0)[0], 255);
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn sniffs_svg_without_extension() {
+        let svg = br#"<?xml version="1.0"?><svg xmlns="http://www.w3.org/2000/svg" width="8" height="4"/>"#;
+        assert!(looks_like_svg(svg));
+        assert!(!looks_like_svg(b"\x89PNG\r\n"));
+
+        let sequence = load_image_from_bytes(svg, Path::new("<stdin>"), &LoadOptions::default()).expect("render");
+        assert_eq!(sequence.first_frame().expect("frame").pixels.dimensions(), (800, 400));
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn renders_svg_at_the_raster_size() {
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="2000" height="1000"><rect width="2000" height="1000" fill="red"/></svg>"#;
+        let render = |options: &LoadOptions| {
+            let sequence = load_image_from_bytes(svg, Path::new("big.svg"), options).expect("render");
+            sequence.first_frame().expect("frame").pixels.clone()
+        };
+
+        let pixels = render(&LoadOptions::default().with_raster_size(320, 320));
+        assert_eq!(pixels.dimensions(), (320, 160));
+        assert_eq!(pixels.get_pixel(319, 159).0, [255, 0, 0, 255]);
+        assert_eq!(render(&LoadOptions::default().with_raster_size(u32::MAX, 40)).dimensions(), (80, 40));
+        // A grid thumbnail wins over the terminal size
+        let thumbnail = LoadOptions::default().with_raster_size(320, 320).with_target_size(50, 50);
+        assert_eq!(render(&thumbnail).dimensions(), (50, 25));
+        assert_eq!(render(&LoadOptions::default()).dimensions(), (2000, 1000));
+    }
 }
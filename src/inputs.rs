@@ -0,0 +1,267 @@
+//! Expansion of directory inputs into the media files they contain
+//!
+//! Explicit file arguments are passed through untouched; only directories
+//! are expanded, filtered and sorted. Files no loader understands are
+//! skipped without a warning, as are hidden entries.
+
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::SortOrder;
+use crate::error::{Result, RimgError};
+use crate::image::{is_supported_media, read_exif_datetime};
+
+#[derive(Debug, Clone, Default)]
+pub struct DirectoryOptions {
+    /// Descend into subdirectories
+    pub recursive: bool,
+    /// Lowercase extensions (without the dot) to keep; empty keeps every supported file
+    pub extensions: Vec<String>,
+    pub sort: SortOrder,
+    /// Seed for `SortOrder::Random`; a time-based seed is used when unset
+    pub seed: Option<u64>,
+}
+
+/// Replace every directory in `inputs` with the media files it contains
+pub fn expand_inputs(inputs: Vec<PathBuf>, options: &DirectoryOptions) -> Result<Vec<PathBuf>> {
+    let mut expanded = Vec::with_capacity(inputs.len());
+
+    for input in inputs {
+        if input.to_str() == Some("-") || !input.is_dir() {
+            expanded.push(input);
+            continue;
+        }
+
+        let mut files = Vec::new();
+        let entries = fs::read_dir(&input).map_err(|err| {
+            RimgError::other(format!("failed to read directory '{}': {}", input.display(), err))
+        })?;
+        collect_files(entries, options, &mut files);
+        sort_files(&mut files, options);
+        expanded.extend(files);
+    }
+
+    Ok(expanded)
+}
+
+fn collect_files(entries: fs::ReadDir, options: &DirectoryOptions, out: &mut Vec<PathBuf>) {
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let hidden = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with('.'));
+        if hidden {
+            continue;
+        }
+
+        // Don't follow directory symlinks while recursing to avoid cycles
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            let children = if options.recursive { fs::read_dir(&path).ok() } else { None };
+            if let Some(children) = children {
+                collect_files(children, options, out);
+            }
+            continue;
+        }
+
+        if path.is_file() && wanted(&path, options) {
+            out.push(path);
+        }
+    }
+}
+
+fn wanted(path: &Path, options: &DirectoryOptions) -> bool {
+    if !options.extensions.is_empty() {
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+        let listed = ext.is_some_and(|ext| options.extensions.contains(&ext));
+        if !listed {
+            return false;
+        }
+    }
+    is_supported_media(path)
+}
+
+fn sort_files(files: &mut [PathBuf], options: &DirectoryOptions) {
+    // Name order first so ties in the other keys stay deterministic
+    files.sort_by(|a, b| natural_cmp(&a.to_string_lossy(), &b.to_string_lossy()));
+
+    match options.sort {
+        SortOrder::Name => {}
+        SortOrder::Mtime => files.sort_by_cached_key(|path| modified(path)),
+        SortOrder::Size => {
+            files.sort_by_cached_key(|path| fs::metadata(path).map(|meta| meta.len()).unwrap_or(0))
+        }
+        SortOrder::Exif => files.sort_by_cached_key(|path| {
+            read_exif_datetime(path).unwrap_or_else(|| format_exif_datetime(modified(path)))
+        }),
+        SortOrder::Random => {
+            let seed = options.seed.unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_nanos() as u64)
+                    .unwrap_or(0)
+            });
+            shuffle(files, seed);
+        }
+    }
+}
+
+fn modified(path: &Path) -> SystemTime {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .unwrap_or(UNIX_EPOCH)
+}
+
+/// Format a timestamp like an EXIF date (UTC) so both sort together
+fn format_exif_datetime(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0) as i64;
+    let (days, rem) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}:{:02}:{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60
+    )
+}
+
+/// Compare strings so embedded numbers sort by value (`img2` < `img10`)
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a_chars, mut b_chars) = (a.chars().peekable(), b.chars().peekable());
+
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let x_digits = take_digits(&mut a_chars);
+                let y_digits = take_digits(&mut b_chars);
+                let x_trimmed = x_digits.trim_start_matches('0');
+                let y_trimmed = y_digits.trim_start_matches('0');
+                let ordering = x_trimmed
+                    .len()
+                    .cmp(&y_trimmed.len())
+                    .then_with(|| x_trimmed.cmp(y_trimmed));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.to_lowercase().cmp(y.to_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> String {
+    let mut digits = String::new();
+    while let Some(ch) = chars.next_if(|ch| ch.is_ascii_digit()) {
+        digits.push(ch);
+    }
+    digits
+}
+
+/// Fisher-Yates shuffle driven by splitmix64, so a seed always gives the same order
+fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    };
+
+    for i in (1..items.len()).rev() {
+        let j = (next() % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempPath;
+
+    fn scratch_dir(name: &str) -> TempPath {
+        let dir = TempPath::dir(name);
+        fs::create_dir_all(dir.join("nested")).expect("create dirs");
+        for file in ["img10.png", "img2.png", "notes.txt", ".hidden.png", "nested/deep.jpg"] {
+            fs::write(dir.join(file), b"").expect("write file");
+        }
+        dir
+    }
+
+    fn names(paths: &[PathBuf]) -> Vec<String> {
+        paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn natural_order_compares_numbers_by_value() {
+        assert_eq!(natural_cmp("img2.png", "img10.png"), Ordering::Less);
+        assert_eq!(natural_cmp("IMG_b", "img_a"), Ordering::Greater);
+        assert_eq!(natural_cmp("a01", "a1"), Ordering::Less);
+    }
+
+    #[test]
+    fn expands_directory_skipping_unsupported_and_hidden() {
+        let dir = scratch_dir("flat");
+        let files = expand_inputs(vec![dir.to_path_buf()], &DirectoryOptions::default()).expect("expand");
+        assert_eq!(names(&files), ["img2.png", "img10.png"]);
+
+        let recursive = DirectoryOptions {
+            recursive: true,
+            extensions: vec!["jpg".to_string()],
+            ..DirectoryOptions::default()
+        };
+        let files = expand_inputs(vec![dir.to_path_buf()], &recursive).expect("expand");
+        assert_eq!(names(&files), ["deep.jpg"]);
+    }
+
+    #[test]
+    fn seeded_shuffle_is_reproducible() {
+        let mut a: Vec<u32> = (0..20).collect();
+        let mut b = a.clone();
+        shuffle(&mut a, 42);
+        shuffle(&mut b, 42);
+        assert_eq!(a, b);
+        assert_ne!(a, (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn formats_timestamps_like_exif() {
+        let time = UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000);
+        assert_eq!(format_exif_datetime(time), "2023:11:14 22:13:20");
+    }
+}
@@ -0,0 +1,140 @@
+//! Graceful handling of SIGINT, SIGTERM and SIGHUP
+//!
+//! Instead of letting the default handler kill the process mid-frame, the
+//! signal is recorded and playback loops poll [`InterruptWatcher::check`].
+//! The resulting [`RimgError::Interrupted`] unwinds through the renderer so
+//! the cursor and alternate-screen guards get to run. A second signal while
+//! the first is still being handled exits immediately.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::{Result, RimgError};
+
+/// Longest uninterrupted sleep while waiting between frames or images
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+#[cfg(unix)]
+const HANDLED_SIGNALS: &[i32] = &[
+    signal_hook::consts::SIGINT,
+    signal_hook::consts::SIGTERM,
+    signal_hook::consts::SIGHUP,
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct InterruptWatcher {
+    triggered: Arc<AtomicBool>,
+    signal: Arc<AtomicUsize>,
+}
+
+/// Keeps the signal handlers installed; dropping it restores the previous behaviour
+pub struct SignalRegistration {
+    #[cfg(unix)]
+    ids: Vec<signal_hook::SigId>,
+}
+
+impl InterruptWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Install handlers for the termination signals
+    ///
+    /// Handlers that fail to install are skipped; the affected signal then
+    /// keeps its default behaviour.
+    #[cfg(unix)]
+    pub fn register(&self) -> SignalRegistration {
+        use signal_hook::flag;
+
+        let mut ids = Vec::with_capacity(HANDLED_SIGNALS.len() * 3);
+        for &signal in HANDLED_SIGNALS {
+            // Registered first so it only fires if an earlier signal already set the flag
+            let forced_exit = 128 + signal;
+            if let Ok(id) = flag::register_conditional_shutdown(signal, forced_exit, Arc::clone(&self.triggered)) {
+                ids.push(id);
+            }
+            if let Ok(id) = flag::register_usize(signal, Arc::clone(&self.signal), signal as usize) {
+                ids.push(id);
+            }
+            if let Ok(id) = flag::register(signal, Arc::clone(&self.triggered)) {
+                ids.push(id);
+            }
+        }
+        SignalRegistration { ids }
+    }
+
+    #[cfg(not(unix))]
+    pub fn register(&self) -> SignalRegistration {
+        SignalRegistration {}
+    }
+
+    /// The signal that interrupted playback, if any
+    pub fn triggered(&self) -> Option<i32> {
+        if self.triggered.load(Ordering::SeqCst) {
+            Some(self.signal.load(Ordering::SeqCst) as i32)
+        } else {
+            None
+        }
+    }
+
+    /// Return `RimgError::Interrupted` once a termination signal has arrived
+    pub fn check(&self) -> Result<()> {
+        match self.triggered() {
+            Some(signal) => Err(RimgError::Interrupted { signal }),
+            None => Ok(()),
+        }
+    }
+
+    /// Sleep for `duration`, waking early if a termination signal arrives
+    pub fn sleep(&self, duration: Duration) -> Result<()> {
+        let deadline = Instant::now() + duration;
+        loop {
+            self.check()?;
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(());
+            }
+            thread::sleep((deadline - now).min(POLL_INTERVAL));
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for SignalRegistration {
+    fn drop(&mut self) {
+        for id in self.ids.drain(..) {
+            signal_hook::low_level::unregister(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_passes_until_triggered() {
+        let watcher = InterruptWatcher::new();
+        assert!(watcher.check().is_ok());
+
+        watcher.signal.store(2, Ordering::SeqCst);
+        watcher.triggered.store(true, Ordering::SeqCst);
+
+        match watcher.check() {
+            Err(RimgError::Interrupted { signal }) => assert_eq!(signal, 2),
+            other => panic!("expected interruption, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn sleep_returns_early_when_triggered() {
+        let watcher = InterruptWatcher::new();
+        watcher.triggered.store(true, Ordering::SeqCst);
+
+        let start = Instant::now();
+        assert!(watcher.sleep(Duration::from_secs(5)).is_err());
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
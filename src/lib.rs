@@ -1,21 +1,39 @@
+pub mod adjust;
+mod ansi;
+#[cfg(feature = "archive")]
+pub mod archive;
 pub mod autocrop;
 pub mod backend;
 pub mod capabilities;
+pub mod cast;
 pub mod cli;
 pub mod color_quantize;
 pub mod config;
 pub mod error;
+pub mod html;
+pub mod icc;
 pub mod image;
+pub mod inputs;
+pub mod interrupt;
+pub mod prefetch;
+pub mod preferences;
+pub mod raster;
+pub mod raw;
 pub mod renderer;
+pub mod resize;
+#[cfg(test)]
+mod test_util;
 pub mod tmux;
+pub mod tonemap;
 #[cfg(feature = "video")]
 pub mod video;
 
 pub use backend::BackendFactory;
 pub use capabilities::{TerminalBackendGuess, TerminalSize, detect_terminal_backend, detect_terminal_name, is_in_multiplexer};
 pub use cli::Cli;
-pub use config::{BackendKind, Config, GridOptions, PixelationMode, RenderSizing, RotationMode};
-pub use error::{Result, RimgError};
-pub use renderer::Renderer;
+pub use config::{BackendKind, Config, CropLength, CropRegion, Flip, GridOptions, OutputFormat, PageSelection, PixelationMode, RenderSizing, ResampleFilter, RotationMode, SortOrder, ToneMapOperator, UnsharpMask};
+pub use error::{Limit, Result, RimgError};
+pub use image::DecodeLimits;
+pub use renderer::{Renderer, RendererBuilder};
 #[cfg(feature = "video")]
 pub use video::VideoLoader;
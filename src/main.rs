@@ -2,12 +2,15 @@ use std::process;
 
 use clap::Parser;
 
-use showme::{Cli, Renderer, Result};
+use showme::{Cli, Renderer, Result, RimgError};
 
 fn main() {
     if let Err(err) = run() {
-        eprintln!("error: {err}");
-        process::exit(1);
+        // The terminal has already been restored; a signal exit needs no message
+        if !matches!(err, RimgError::Interrupted { .. }) {
+            eprintln!("error: {err}");
+        }
+        process::exit(err.exit_code());
     }
 }
 
@@ -0,0 +1,219 @@
+//! Persistent defaults from the config file and the `SHOWME_OPTS` variable
+//!
+//! Both sources produce a [`Preferences`] layer. Layers are merged with
+//! [`Preferences::or`], so explicit command-line flags win over
+//! `SHOWME_OPTS`, which wins over the matching `[terminal.<name>]` section,
+//! which wins over the top level of the config file.
+//!
+//! ```toml
+//! backend = "unicode"
+//! pixelation = "half"
+//! background = "#202020"
+//!
+//! [terminal.WezTerm]
+//! backend = "iterm2"
+//! width-stretch = 2.1
+//! ```
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::config::{BackendKind, PixelationMode, RotationMode};
+use crate::error::{Result, RimgError};
+
+/// Environment variable holding default command-line options
+pub const OPTIONS_ENV: &str = "SHOWME_OPTS";
+
+/// One layer of user defaults; `None` means "not set in this layer"
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Preferences {
+    #[serde(default, deserialize_with = "from_str_opt")]
+    pub backend: Option<BackendKind>,
+    #[serde(default, deserialize_with = "from_str_opt")]
+    pub pixelation: Option<PixelationMode>,
+    #[serde(default, deserialize_with = "from_str_opt")]
+    pub rotate: Option<RotationMode>,
+    pub background: Option<String>,
+    pub pattern: Option<String>,
+    pub pattern_size: Option<u16>,
+    pub width_stretch: Option<f32>,
+    pub compress: Option<u8>,
+    pub threads: Option<usize>,
+    pub title: Option<String>,
+    pub center: Option<bool>,
+    pub alternate_screen: Option<bool>,
+    pub color8: Option<bool>,
+    pub quiet: Option<bool>,
+}
+
+impl Preferences {
+    /// Fill every unset value from `fallback`
+    pub fn or(self, fallback: Preferences) -> Preferences {
+        Preferences {
+            backend: self.backend.or(fallback.backend),
+            pixelation: self.pixelation.or(fallback.pixelation),
+            rotate: self.rotate.or(fallback.rotate),
+            background: self.background.or(fallback.background),
+            pattern: self.pattern.or(fallback.pattern),
+            pattern_size: self.pattern_size.or(fallback.pattern_size),
+            width_stretch: self.width_stretch.or(fallback.width_stretch),
+            compress: self.compress.or(fallback.compress),
+            threads: self.threads.or(fallback.threads),
+            title: self.title.or(fallback.title),
+            center: self.center.or(fallback.center),
+            alternate_screen: self.alternate_screen.or(fallback.alternate_screen),
+            color8: self.color8.or(fallback.color8),
+            quiet: self.quiet.or(fallback.quiet),
+        }
+    }
+}
+
+/// Parsed `config.toml`: global defaults plus per-terminal sections
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigFile {
+    pub defaults: Preferences,
+    /// Sections keyed by the name `detect_terminal_name()` reports
+    pub terminals: HashMap<String, Preferences>,
+}
+
+impl ConfigFile {
+    /// Load the config file at `path`, returning `None` if it does not exist
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(RimgError::other(format!(
+                    "failed to read config file '{}': {}",
+                    path.display(),
+                    err
+                )));
+            }
+        };
+        Self::parse(&text, path).map(Some)
+    }
+
+    /// Parse config file contents; `origin` is only used in error messages
+    pub fn parse(text: &str, origin: &Path) -> Result<Self> {
+        let invalid = |err: &dyn Display| {
+            RimgError::other(format!("invalid config file '{}': {}", origin.display(), err))
+        };
+
+        let mut table: toml::Table = text.parse().map_err(|err| invalid(&err))?;
+
+        let mut terminals = HashMap::new();
+        if let Some(sections) = table.remove("terminal") {
+            let toml::Value::Table(sections) = sections else {
+                return Err(invalid(&"`terminal` must be a table of sections"));
+            };
+            for (name, section) in sections {
+                let prefs: Preferences = section
+                    .try_into()
+                    .map_err(|err| invalid(&format!("[terminal.{}]: {}", name, err)))?;
+                terminals.insert(name, prefs);
+            }
+        }
+
+        let defaults: Preferences = toml::Value::Table(table)
+            .try_into()
+            .map_err(|err| invalid(&err))?;
+
+        Ok(Self { defaults, terminals })
+    }
+
+    /// Defaults for `terminal`, with its section (matched case-insensitively) layered on top
+    pub fn preferences_for(&self, terminal: Option<&str>) -> Preferences {
+        let section = terminal.and_then(|name| {
+            self.terminals
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, prefs)| prefs.clone())
+        });
+
+        match section {
+            Some(section) => section.or(self.defaults.clone()),
+            None => self.defaults.clone(),
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/showme/config.toml`, falling back to `~/.config/showme/config.toml`
+pub fn default_config_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("showme").join("config.toml"))
+}
+
+fn from_str_opt<'de, D, T>(deserializer: D) -> std::result::Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr<Err = String>,
+{
+    let raw = String::deserialize(deserializer)?;
+    T::from_str(&raw).map(Some).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_defaults_and_terminal_sections() {
+        let file = ConfigFile::parse(
+            r##"
+                backend = "unicode"
+                pixelation = "half"
+                background = "#202020"
+
+                [terminal.WezTerm]
+                backend = "iterm2"
+                width-stretch = 2.1
+            "##,
+            Path::new("config.toml"),
+        )
+        .expect("config parses");
+
+        assert_eq!(file.defaults.backend, Some(BackendKind::Unicode));
+        assert_eq!(file.defaults.pixelation, Some(PixelationMode::Half));
+
+        let wezterm = file.preferences_for(Some("wezterm"));
+        assert_eq!(wezterm.backend, Some(BackendKind::Iterm2));
+        assert_eq!(wezterm.pixelation, Some(PixelationMode::Half));
+        assert_eq!(wezterm.width_stretch, Some(2.1));
+
+        let other = file.preferences_for(Some("xterm-256color"));
+        assert_eq!(other.backend, Some(BackendKind::Unicode));
+        assert_eq!(other.width_stretch, None);
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_bad_values() {
+        assert!(ConfigFile::parse("bakend = \"kitty\"", Path::new("c.toml")).is_err());
+        assert!(ConfigFile::parse("backend = \"braille\"", Path::new("c.toml")).is_err());
+    }
+
+    #[test]
+    fn earlier_layer_wins() {
+        let explicit = Preferences {
+            backend: Some(BackendKind::Kitty),
+            ..Preferences::default()
+        };
+        let stored = Preferences {
+            backend: Some(BackendKind::Unicode),
+            quiet: Some(true),
+            ..Preferences::default()
+        };
+
+        let merged = explicit.or(stored);
+        assert_eq!(merged.backend, Some(BackendKind::Kitty));
+        assert_eq!(merged.quiet, Some(true));
+    }
+}
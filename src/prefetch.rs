@@ -0,0 +1,244 @@
+//! Decoding ahead of the display, in input order
+//!
+//! [`Prefetch`] expands and decodes inputs on the rayon pool while earlier
+//! images are on screen and yields them in input order as soon as the next
+//! one is ready. No more than `depth` inputs or images are being decoded or
+//! waiting to be shown at once, so memory stays flat however many files are
+//! given; the caller drops each sequence after showing it. PDF pages are
+//! rendered ahead the same way, one job per page.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+use crate::config::PageSelection;
+use crate::error::{Result, RimgError};
+use crate::image::{ImageSequence, LoadOptions, PendingImage, expand_input};
+use crate::interrupt::InterruptWatcher;
+
+/// What [`Prefetch`] has handed out so far
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoadStats {
+    pub images: usize,
+    pub frames: usize,
+    /// Time spent decoding, summed over the pool's threads
+    pub decode_time: Duration,
+}
+
+enum Slot {
+    /// An input being expanded into its images
+    Expanding(Receiver<(Result<Vec<PendingImage>>, Duration)>),
+    /// A PDF page that hasn't been started yet
+    Deferred(PendingImage),
+    /// A PDF page being rendered
+    Loading(Receiver<(Result<ImageSequence>, Duration)>),
+    Ready(ImageSequence),
+}
+
+impl Slot {
+    /// Whether the slot holds, or is producing, decoded pixels
+    fn is_busy(&self) -> bool {
+        !matches!(self, Self::Deferred(_))
+    }
+}
+
+/// Iterator over the images behind a list of inputs, decoded ahead of time
+pub struct Prefetch {
+    inputs: std::vec::IntoIter<PathBuf>,
+    queue: VecDeque<Slot>,
+    options: Arc<LoadOptions>,
+    pages: Option<Arc<PageSelection>>,
+    pool: Option<Arc<rayon::ThreadPool>>,
+    interrupt: InterruptWatcher,
+    depth: usize,
+    stats: LoadStats,
+    peeked: Option<Option<Result<ImageSequence>>>,
+}
+
+impl Prefetch {
+    /// Decode `inputs` with `options`, keeping up to `depth` of them in flight
+    pub fn new(inputs: Vec<PathBuf>, options: LoadOptions, depth: usize) -> Self {
+        Self {
+            inputs: inputs.into_iter(),
+            queue: VecDeque::new(),
+            options: Arc::new(options),
+            pages: None,
+            pool: None,
+            interrupt: InterruptWatcher::new(),
+            depth: depth.max(1),
+            stats: LoadStats::default(),
+            peeked: None,
+        }
+    }
+
+    /// Show only these pages of PDF inputs
+    pub fn with_pages(mut self, pages: Option<PageSelection>) -> Self {
+        self.pages = pages.map(Arc::new);
+        self
+    }
+
+    /// Decode on `pool` instead of the global rayon pool
+    pub fn with_pool(mut self, pool: rayon::ThreadPool) -> Self {
+        self.pool = Some(Arc::new(pool));
+        self
+    }
+
+    /// Skip jobs that start after `interrupt` fired
+    pub fn with_interrupt(mut self, interrupt: InterruptWatcher) -> Self {
+        self.interrupt = interrupt;
+        self
+    }
+
+    pub fn stats(&self) -> LoadStats {
+        self.stats
+    }
+
+    /// The next image without taking it, waiting for it to be decoded
+    pub fn peek(&mut self) -> Option<&Result<ImageSequence>> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.advance());
+        }
+        self.peeked.as_ref().and_then(Option::as_ref)
+    }
+
+    fn spawn<T: Send + 'static>(
+        &self,
+        job: impl FnOnce(&LoadOptions) -> Result<T> + Send + 'static,
+    ) -> Receiver<(Result<T>, Duration)> {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let options = Arc::clone(&self.options);
+        let interrupt = self.interrupt.clone();
+        let run = move || {
+            let started = Instant::now();
+            let result = interrupt.check().and_then(|()| job(&options));
+            // The receiver is gone when the display stopped early
+            let _ = sender.send((result, started.elapsed()));
+        };
+        match &self.pool {
+            Some(pool) => pool.spawn(run),
+            None => rayon::spawn(run),
+        }
+        receiver
+    }
+
+    /// Start work until `depth` slots are busy, earlier images first
+    fn fill(&mut self) {
+        let mut busy = self.queue.iter().filter(|slot| slot.is_busy()).count();
+        for index in 0..self.queue.len() {
+            if busy >= self.depth {
+                return;
+            }
+            if let Slot::Deferred(image) = &self.queue[index] {
+                // Pages share the document bytes, so the copy is cheap
+                let image = image.clone();
+                let loading = Slot::Loading(self.spawn(move |options| image.load(options)));
+                self.queue[index] = loading;
+                busy += 1;
+            }
+        }
+        // Later inputs wait until every page of the earlier ones is started
+        while busy < self.depth && !self.queue.iter().any(|slot| matches!(slot, Slot::Deferred(_))) {
+            let Some(path) = self.inputs.next() else {
+                return;
+            };
+            let pages = self.pages.clone();
+            let expanding = self.spawn(move |options| expand_input(&path, options, pages.as_deref()));
+            self.queue.push_back(Slot::Expanding(expanding));
+            busy += 1;
+        }
+    }
+
+    fn receive<T>(&mut self, receiver: Receiver<(Result<T>, Duration)>) -> Result<T> {
+        let (result, elapsed) = receiver
+            .recv()
+            .map_err(|_| RimgError::other("image decoder stopped without a result"))?;
+        self.stats.decode_time += elapsed;
+        result
+    }
+
+    fn finish(&mut self, sequence: ImageSequence) -> Option<Result<ImageSequence>> {
+        self.stats.images += 1;
+        self.stats.frames += sequence.frames.len();
+        Some(Ok(sequence))
+    }
+
+    fn advance(&mut self) -> Option<Result<ImageSequence>> {
+        loop {
+            self.fill();
+            match self.queue.pop_front()? {
+                Slot::Expanding(receiver) => {
+                    let images = match self.receive(receiver) {
+                        Ok(images) => images,
+                        Err(err) => return Some(Err(err)),
+                    };
+                    for image in images.into_iter().rev() {
+                        self.queue.push_front(match image {
+                            PendingImage::Ready(sequence) => Slot::Ready(sequence),
+                            deferred => Slot::Deferred(deferred),
+                        });
+                    }
+                }
+                Slot::Deferred(image) => {
+                    let started = Instant::now();
+                    let result = image.load(&self.options);
+                    self.stats.decode_time += started.elapsed();
+                    return match result {
+                        Ok(sequence) => self.finish(sequence),
+                        Err(err) => Some(Err(err)),
+                    };
+                }
+                Slot::Loading(receiver) => {
+                    return match self.receive(receiver) {
+                        Ok(sequence) => self.finish(sequence),
+                        Err(err) => Some(Err(err)),
+                    };
+                }
+                Slot::Ready(sequence) => return self.finish(sequence),
+            }
+        }
+    }
+}
+
+impl Iterator for Prefetch {
+    type Item = Result<ImageSequence>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.peeked.take() {
+            Some(peeked) => peeked,
+            None => self.advance(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempPath;
+    use image::{Rgba, RgbaImage};
+
+    #[test]
+    fn yields_images_in_input_order() {
+        let dir = TempPath::dir("prefetch");
+        let mut inputs = Vec::new();
+        for width in 1..=12 {
+            let path = dir.join(format!("{width}.png"));
+            RgbaImage::from_pixel(width, 1, Rgba([0, 0, 0, 255])).save(&path).expect("save");
+            inputs.push(path);
+        }
+        inputs.insert(5, dir.join("missing.png"));
+
+        let mut prefetch = Prefetch::new(inputs, LoadOptions::default(), 3);
+        assert_eq!(prefetch.peek().map(|result| result.is_ok()), Some(true));
+        let widths: Vec<_> = prefetch
+            .by_ref()
+            .map(|result| result.map(|sequence| sequence.frames[0].pixels.width()).ok())
+            .collect();
+
+        let mut expected: Vec<_> = (1..=12).map(Some).collect();
+        expected.insert(5, None);
+        assert_eq!(widths, expected);
+        assert_eq!((prefetch.stats().images, prefetch.stats().frames), (12, 12));
+    }
+}
@@ -0,0 +1,209 @@
+//! Rasterisation of Unicode block art into a picture of what the terminal showed
+//!
+//! Block elements are pure geometry, so a [`RenderedFrame`] from the Unicode
+//! backend can be painted cell by cell without a font: every cell becomes a
+//! `cell_width`×`cell_height` rectangle split between its foreground and
+//! background colours. Cells without a background colour stay transparent.
+//! Any other character (there are none in image output) only gets its
+//! background painted.
+
+use std::io::Write;
+
+use image::codecs::png::PngEncoder;
+use image::{ImageEncoder, Rgba, RgbaImage};
+
+use crate::ansi::SgrState;
+use crate::backend::RenderedFrame;
+use crate::error::{Result, RimgError};
+
+/// Cell size used when none is configured, in pixels
+pub const DEFAULT_CELL_SIZE: (u32, u32) = (8, 16);
+
+/// Foreground for glyphs drawn before any colour was selected
+const DEFAULT_FG: [u8; 3] = [229, 229, 229];
+
+/// The part of a cell a block element covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    /// Bit 0 top-left, 1 top-right, 2 bottom-left, 3 bottom-right
+    Quadrants(u8),
+    /// Bottom `n` eighths
+    Lower(u32),
+    /// Top `n` eighths
+    Upper(u32),
+    /// Left `n` eighths
+    Left(u32),
+    /// Right `n` eighths
+    Right(u32),
+    /// Foreground mixed over the whole cell at `n` quarters strength
+    Shade(u32),
+}
+
+fn shape(ch: char) -> Option<Shape> {
+    let shape = match ch {
+        ' ' => Shape::Quadrants(0b0000),
+        '▘' => Shape::Quadrants(0b0001),
+        '▝' => Shape::Quadrants(0b0010),
+        '▀' => Shape::Quadrants(0b0011),
+        '▖' => Shape::Quadrants(0b0100),
+        '▌' => Shape::Quadrants(0b0101),
+        '▞' => Shape::Quadrants(0b0110),
+        '▛' => Shape::Quadrants(0b0111),
+        '▗' => Shape::Quadrants(0b1000),
+        '▚' => Shape::Quadrants(0b1001),
+        '▐' => Shape::Quadrants(0b1010),
+        '▜' => Shape::Quadrants(0b1011),
+        '▄' => Shape::Quadrants(0b1100),
+        '▙' => Shape::Quadrants(0b1101),
+        '▟' => Shape::Quadrants(0b1110),
+        '█' => Shape::Quadrants(0b1111),
+        '▁'..='▇' => Shape::Lower(ch as u32 - 0x2580),
+        '▔' => Shape::Upper(1),
+        '▉'..='▏' => Shape::Left(8 - (ch as u32 - 0x2588)),
+        '▕' => Shape::Right(1),
+        '░' => Shape::Shade(1),
+        '▒' => Shape::Shade(2),
+        '▓' => Shape::Shade(3),
+        _ => return None,
+    };
+    Some(shape)
+}
+
+impl Shape {
+    /// How much of the foreground shows at pixel (`x`, `y`) of a `width`×`height` cell, out of 4
+    fn coverage(self, x: u32, y: u32, width: u32, height: u32) -> u32 {
+        let eighths = |n: u32, extent: u32| (extent * n + 4) / 8;
+        let covered = match self {
+            Shape::Quadrants(mask) => {
+                let column = u32::from(x * 2 >= width);
+                let row = u32::from(y * 2 >= height);
+                mask & (1 << (row * 2 + column)) != 0
+            }
+            Shape::Lower(n) => y >= height - eighths(n, height),
+            Shape::Upper(n) => y < eighths(n, height),
+            Shape::Left(n) => x < eighths(n, width),
+            Shape::Right(n) => x >= width - eighths(n, width),
+            Shape::Shade(quarters) => return quarters,
+        };
+        if covered { 4 } else { 0 }
+    }
+}
+
+/// Paint `frame` with every terminal cell `cell_width`×`cell_height` pixels large
+pub fn rasterize_frame(frame: &RenderedFrame, cell_width: u32, cell_height: u32) -> RgbaImage {
+    let cell_width = cell_width.max(1);
+    let cell_height = cell_height.max(1);
+
+    let rows: Vec<Vec<(Option<Shape>, SgrState)>> = frame.lines.iter().map(|line| parse_cells(line)).collect();
+    let columns = rows
+        .iter()
+        .map(Vec::len)
+        .max()
+        .unwrap_or(0)
+        .max(frame.width_cells as usize) as u32;
+
+    let mut image = RgbaImage::new(columns * cell_width, rows.len() as u32 * cell_height);
+    for (row, cells) in rows.iter().enumerate() {
+        for (column, (shape, style)) in cells.iter().enumerate() {
+            let origin_x = column as u32 * cell_width;
+            let origin_y = row as u32 * cell_height;
+            let fg = style.fg.unwrap_or(DEFAULT_FG);
+            for y in 0..cell_height {
+                for x in 0..cell_width {
+                    let coverage = shape.map_or(0, |shape| shape.coverage(x, y, cell_width, cell_height));
+                    image.put_pixel(origin_x + x, origin_y + y, mix(fg, style.bg, coverage));
+                }
+            }
+        }
+    }
+    image
+}
+
+/// Encode `image` as PNG into `out`
+pub fn write_png(image: &RgbaImage, out: &mut dyn Write) -> Result<()> {
+    PngEncoder::new(out)
+        .write_image(image.as_raw(), image.width(), image.height(), image::ColorType::Rgba8.into())
+        .map_err(|err| RimgError::other(format!("failed to encode PNG: {}", err)))
+}
+
+/// Foreground over background at `coverage` quarters; a missing background is transparent
+fn mix(fg: [u8; 3], bg: Option<[u8; 3]>, coverage: u32) -> Rgba<u8> {
+    match bg {
+        _ if coverage >= 4 => Rgba([fg[0], fg[1], fg[2], 255]),
+        Some(bg) => {
+            let blend = |f: u8, b: u8| ((u32::from(f) * coverage + u32::from(b) * (4 - coverage)) / 4) as u8;
+            Rgba([blend(fg[0], bg[0]), blend(fg[1], bg[1]), blend(fg[2], bg[2]), 255])
+        }
+        None if coverage == 0 => Rgba([0, 0, 0, 0]),
+        None => Rgba([fg[0], fg[1], fg[2], (coverage * 255 / 4) as u8]),
+    }
+}
+
+/// Split one rendered line into cells with the colours in effect for each
+fn parse_cells(line: &str) -> Vec<(Option<Shape>, SgrState)> {
+    let mut cells = Vec::new();
+    let mut style = SgrState::default();
+    let mut chars = line.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' {
+            // Only CSI sequences appear inside Unicode output; SGR is the one that matters
+            if chars.next() == Some('[') {
+                let mut params = String::new();
+                for next in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&next) {
+                        if next == 'm' {
+                            style.apply(&params);
+                        }
+                        break;
+                    }
+                    params.push(next);
+                }
+            }
+            continue;
+        }
+        if ch.is_control() {
+            continue;
+        }
+        cells.push((shape(ch), style));
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn frame(lines: &[&str]) -> RenderedFrame {
+        RenderedFrame {
+            lines: lines.iter().map(|line| line.to_string()).collect(),
+            width_cells: 0,
+            height_cells: lines.len() as u32,
+            delay: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn paints_half_blocks_with_foreground_and_background() {
+        let image = rasterize_frame(&frame(&["\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m▀\x1b[0m "]), 2, 4);
+        assert_eq!(image.dimensions(), (4, 4));
+        assert_eq!(*image.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(1, 1), Rgba([255, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(0, 2), Rgba([0, 0, 255, 255]));
+        assert_eq!(*image.get_pixel(3, 3), Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn maps_quadrants_and_eighths() {
+        assert_eq!(shape('▚'), Some(Shape::Quadrants(0b1001)));
+        assert_eq!(shape('▂'), Some(Shape::Lower(2)));
+        assert_eq!(shape('▊'), Some(Shape::Left(6)));
+        assert_eq!(shape('▏'), Some(Shape::Left(1)));
+        assert_eq!(shape('x'), None);
+
+        let image = rasterize_frame(&frame(&["\x1b[38;5;15m▗"]), 4, 4);
+        assert_eq!(image.get_pixel(0, 0)[3], 0);
+        assert_eq!(*image.get_pixel(3, 3), Rgba([255, 255, 255, 255]));
+    }
+}
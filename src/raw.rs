@@ -0,0 +1,303 @@
+//! Camera RAW previews
+//!
+//! TIFF-based RAW formats (CR2, NEF, ARW, DNG, ORF, RW2, PEF, …) carry one or
+//! more full JPEG renditions next to the sensor data. Rather than demosaicing,
+//! the loader shows the largest of those previews. The IFDs are parsed with
+//! the same EXIF reader used for orientation tags; previews referenced from
+//! `SubIFDs` are reached by re-parsing the file with IFD0 pointed at them.
+
+use std::path::Path;
+
+use exif::{Context, Field, In, Tag, Value};
+
+use crate::error::{Result, RimgError};
+
+/// `SubIFDs`, which the EXIF reader keeps as a plain field instead of following
+const SUB_IFDS: Tag = Tag(Context::Tiff, 0x14a);
+
+/// Most `SubIFDs` followed; cameras use a handful, so more means a forged file
+const MAX_SUB_IFDS: usize = 16;
+
+/// Old-style JPEG and JPEG compression values
+const JPEG_COMPRESSION: [u32; 2] = [6, 7];
+
+const RAW_EXTENSIONS: &[&str] = &[
+    "3fr", "arw", "cr2", "dcr", "dng", "erf", "iiq", "k25", "kdc", "mef", "mos", "nef", "nrw", "orf", "pef",
+    "raw", "rw2", "sr2", "srf", "srw",
+];
+
+/// The embedded JPEG chosen for display and the RAW's orientation tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawPreview<'a> {
+    pub jpeg: &'a [u8],
+    pub orientation: Option<u32>,
+}
+
+/// Whether `path` has the extension of a TIFF-based RAW format
+pub fn is_raw_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| RAW_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// Whether `bytes` is a RAW file that needs [`extract_preview`]
+///
+/// Ordinary TIFFs share the header, so they only count with a RAW extension;
+/// Canon's `CR` marker and the Olympus/Panasonic header variants are
+/// recognised on their own.
+pub fn is_raw(bytes: &[u8], hint: &Path) -> bool {
+    let Some(header) = bytes.get(..10) else {
+        return false;
+    };
+    let tiff = matches!(&header[..4], b"II*\0" | b"MM\0*");
+    let variant = matches!(&header[..4], b"IIRO" | b"IIRS" | b"MMOR" | b"IIU\0");
+    let canon = tiff && &header[8..10] == b"CR";
+    variant || canon || (tiff && is_raw_path(hint))
+}
+
+/// Find the largest embedded JPEG preview in a RAW file
+pub fn extract_preview<'a>(bytes: &'a [u8], path: &Path) -> Result<RawPreview<'a>> {
+    let invalid = |err: exif::Error| RimgError::other(format!("RAW: failed to read '{}': {}", path.display(), err));
+    let little_endian = bytes.starts_with(b"II");
+    let mut tiff = Tiff::new(bytes).map_err(invalid)?;
+    let root = tiff.read_ifds(None).map_err(invalid)?;
+    let orientation = get_field(&root, Tag::Orientation, In::PRIMARY).and_then(|field| field.value.get_uint(0));
+
+    let mut candidates = previews(&root, bytes);
+    let sub_ifds = root
+        .iter()
+        .filter(|field| field.tag == SUB_IFDS)
+        .flat_map(|field| uints(&field.value, bytes, little_endian))
+        .take(MAX_SUB_IFDS);
+    for offset in sub_ifds {
+        if let Ok(sub) = tiff.read_ifds(Some(offset)) {
+            candidates.extend(previews(&sub, bytes));
+        }
+    }
+
+    let jpeg = candidates
+        .into_iter()
+        .max_by_key(|jpeg| jpeg.len())
+        .ok_or_else(|| RimgError::other(format!("RAW: '{}' has no embedded JPEG preview", path.display())))?;
+    Ok(RawPreview { jpeg, orientation })
+}
+
+/// A copy of the file with a standard TIFF header, which the EXIF reader
+/// parses from any IFD by patching the header's IFD0 offset
+struct Tiff {
+    data: Vec<u8>,
+    little_endian: bool,
+}
+
+impl Tiff {
+    fn new(bytes: &[u8]) -> std::result::Result<Self, exif::Error> {
+        let mut data = bytes.to_vec();
+        if data.len() < 8 {
+            return Err(exif::Error::InvalidFormat("Truncated TIFF header"));
+        }
+        // Olympus and Panasonic use their own magic in place of 42
+        let little_endian = data.starts_with(b"II");
+        let magic = if little_endian { 42u16.to_le_bytes() } else { 42u16.to_be_bytes() };
+        data[..2].copy_from_slice(if little_endian { b"II" } else { b"MM" });
+        data[2..4].copy_from_slice(&magic);
+        Ok(Self { data, little_endian })
+    }
+
+    /// Parse the IFD chain, optionally starting at `ifd0` instead of the header's offset
+    fn read_ifds(&mut self, ifd0: Option<u32>) -> std::result::Result<Vec<Field>, exif::Error> {
+        if let Some(offset) = ifd0 {
+            let offset = if self.little_endian { offset.to_le_bytes() } else { offset.to_be_bytes() };
+            self.data[4..8].copy_from_slice(&offset);
+        }
+        exif::parse_exif(&self.data).map(|(fields, _)| fields)
+    }
+}
+
+fn get_field(fields: &[Field], tag: Tag, ifd: In) -> Option<&Field> {
+    fields.iter().find(|field| field.tag == tag && field.ifd_num == ifd)
+}
+
+/// JPEG streams referenced by each IFD in `fields`
+fn previews<'a>(fields: &[Field], bytes: &'a [u8]) -> Vec<&'a [u8]> {
+    let little_endian = bytes.starts_with(b"II");
+    let mut found = Vec::new();
+    let mut ifds: Vec<In> = Vec::new();
+    for field in fields {
+        if !ifds.contains(&field.ifd_num) {
+            ifds.push(field.ifd_num);
+        }
+    }
+
+    for ifd in ifds {
+        let uint = |tag: Tag| get_field(fields, tag, ifd).and_then(|field| field.value.get_uint(0));
+        let sum = |tag: Tag| {
+            get_field(fields, tag, ifd)
+                .map(|field| uints(&field.value, bytes, little_endian).iter().map(|&n| n as usize).sum::<usize>())
+        };
+
+        if let (Some(offset), Some(length)) = (
+            uint(Tag::JPEGInterchangeFormat),
+            uint(Tag::JPEGInterchangeFormatLength),
+        ) {
+            found.extend(jpeg_at(bytes, offset as usize, length as usize));
+        }
+
+        let compressed = uint(Tag::Compression).is_some_and(|value| JPEG_COMPRESSION.contains(&value));
+        if let (true, Some(offset), Some(length)) = (compressed, uint(Tag::StripOffsets), sum(Tag::StripByteCounts)) {
+            found.extend(jpeg_at(bytes, offset as usize, length));
+        }
+    }
+    found
+}
+
+/// All unsigned values of a field, including ones too large for the EXIF reader to inline
+fn uints(value: &Value, bytes: &[u8], little_endian: bool) -> Vec<u32> {
+    match value {
+        Value::Short(values) => values.iter().map(|&v| u32::from(v)).collect(),
+        Value::Long(values) => values.clone(),
+        // IFD-typed fields stay raw: one offset inline, or a pointer to an array of them.
+        // The count is unchecked, so it is clamped to what the file can hold.
+        Value::Unknown(13, count, at) => {
+            let at = *at as usize;
+            let start = if *count == 1 {
+                Some(at)
+            } else {
+                read_u32(bytes, at, little_endian).map(|start| start as usize)
+            };
+            start
+                .map(|start| {
+                    let count = (*count as usize).min(bytes.len().saturating_sub(start) / 4);
+                    (0..count)
+                        .map_while(|index| read_u32(bytes, start + index * 4, little_endian))
+                        .collect()
+                })
+                .unwrap_or_default()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn read_u32(bytes: &[u8], at: usize, little_endian: bool) -> Option<u32> {
+    let raw: [u8; 4] = bytes.get(at..at + 4)?.try_into().ok()?;
+    Some(if little_endian {
+        u32::from_le_bytes(raw)
+    } else {
+        u32::from_be_bytes(raw)
+    })
+}
+
+/// The slice at `offset` if it is a baseline or progressive JPEG
+fn jpeg_at(bytes: &[u8], offset: usize, length: usize) -> Option<&[u8]> {
+    let jpeg = bytes.get(offset..offset.checked_add(length)?)?;
+    (jpeg.starts_with(&[0xFF, 0xD8]) && is_viewable_jpeg(jpeg)).then_some(jpeg)
+}
+
+/// Whether the first frame header is SOF0–SOF2
+///
+/// Sensor data in CR2 and DNG files is stored as lossless JPEG (SOF3), which
+/// image decoders can't display and must not win the size comparison.
+fn is_viewable_jpeg(jpeg: &[u8]) -> bool {
+    let mut at = 2;
+    while let Some(&[0xFF, marker]) = jpeg.get(at..at + 2) {
+        match marker {
+            // Fill bytes before a marker
+            0xFF => at += 1,
+            0xC0..=0xC2 => return true,
+            0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF | 0xDA => return false,
+            _ => {
+                let Some(&[high, low]) = jpeg.get(at + 2..at + 4) else {
+                    return false;
+                };
+                at += 2 + usize::from(u16::from_be_bytes([high, low]));
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::jpeg::JpegEncoder;
+
+    fn jpeg(width: u32) -> Vec<u8> {
+        let mut out = Vec::new();
+        let pixels = image::RgbImage::from_pixel(width, 8, image::Rgb([40, 90, 200]));
+        JpegEncoder::new(&mut out).encode_image(&pixels).expect("encode jpeg");
+        out
+    }
+
+    /// Little-endian TIFF: IFD0 (orientation, small preview, SubIFDs) and one SubIFD with a big preview
+    fn fake_raw(small: &[u8], big: &[u8]) -> Vec<u8> {
+        fn entry(out: &mut Vec<u8>, tag: u16, typ: u16, count: u32, value: u32) {
+            out.extend_from_slice(&tag.to_le_bytes());
+            out.extend_from_slice(&typ.to_le_bytes());
+            out.extend_from_slice(&count.to_le_bytes());
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let ifd0 = 8u32;
+        let ifd0_len = 2 + 4 * 12 + 4;
+        let sub = ifd0 + ifd0_len;
+        let sub_len = 2 + 2 * 12 + 4;
+        let small_at = sub + sub_len;
+        let big_at = small_at + small.len() as u32;
+
+        let mut out = b"II*\0".to_vec();
+        out.extend_from_slice(&ifd0.to_le_bytes());
+        out.extend_from_slice(&4u16.to_le_bytes());
+        entry(&mut out, 0x112, 3, 1, 6);
+        entry(&mut out, 0x14a, 13, 1, sub);
+        entry(&mut out, 0x201, 4, 1, small_at);
+        entry(&mut out, 0x202, 4, 1, small.len() as u32);
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.extend_from_slice(&2u16.to_le_bytes());
+        entry(&mut out, 0x201, 4, 1, big_at);
+        entry(&mut out, 0x202, 4, 1, big.len() as u32);
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.extend_from_slice(small);
+        out.extend_from_slice(big);
+        out
+    }
+
+    #[test]
+    fn recognises_raw_files_but_not_plain_tiffs() {
+        let header = b"II*\0\x10\0\0\0CR\x02\0";
+        assert!(is_raw(header, Path::new("<stdin>")));
+        assert!(is_raw(b"IIU\0\x08\0\0\0\0\0", Path::new("shot.rw2")));
+        assert!(is_raw(b"MM\0*\0\0\0\x08\0\0", Path::new("DSC_0001.NEF")));
+        assert!(!is_raw(b"MM\0*\0\0\0\x08\0\0", Path::new("scan.tif")));
+    }
+
+    #[test]
+    fn picks_the_largest_preview_from_sub_ifds() {
+        let (small, big) = (jpeg(8), jpeg(64));
+        let raw = fake_raw(&small, &big);
+        let preview = extract_preview(&raw, Path::new("shot.nef")).expect("preview");
+        assert_eq!(preview.jpeg, big.as_slice());
+        assert_eq!(preview.orientation, Some(6));
+
+        let mut lossless = big.clone();
+        let sof = lossless.windows(2).position(|pair| pair == [0xFF, 0xC0]).expect("SOF0");
+        lossless[sof + 1] = 0xC3;
+        let raw = fake_raw(&small, &lossless);
+        let preview = extract_preview(&raw, Path::new("shot.dng")).expect("preview");
+        assert_eq!(preview.jpeg, small.as_slice());
+    }
+
+    #[test]
+    fn clamps_forged_sub_ifd_counts_to_the_file() {
+        let (small, big) = (jpeg(8), jpeg(64));
+        let mut raw = fake_raw(&small, &big);
+        // Turn the inline SubIFDs offset into a pointer to a one-entry array
+        // that claims four billion entries
+        let sub = raw[30..34].to_vec();
+        let array = raw.len() as u32;
+        raw.extend_from_slice(&sub);
+        raw[26..30].copy_from_slice(&u32::MAX.to_le_bytes());
+        raw[30..34].copy_from_slice(&array.to_le_bytes());
+
+        let preview = extract_preview(&raw, Path::new("shot.nef")).expect("preview");
+        assert_eq!(preview.jpeg, big.as_slice());
+    }
+}
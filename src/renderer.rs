@@ -1,47 +1,180 @@
-use std::io::{self, Write};
-use std::thread;
-use std::time::Duration;
-
-use rayon::prelude::*;
-
-use crate::backend::{Backend, BackendFactory, BackgroundStyle, RenderOptions};
-use crate::capabilities::{current_terminal_size, detect_terminal_backend, ensure_tty_stdout};
-use crate::config::{BackendKind, BackgroundColor, Config};
+use std::io::{self, IsTerminal, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::adjust::Adjustments;
+use crate::backend::{Backend, BackendFactory, BackgroundStyle, HtmlImageBackend, RenderOptions, UnicodeBackend};
+use crate::capabilities::{
+    TerminalSize, current_terminal_size, detect_terminal_backend, ensure_tty_stdout, fixed_terminal_size,
+};
+use crate::cast::{CastClock, CastWriter};
+use crate::config::{BackendKind, BackgroundColor, Config, OutputFormat};
 use crate::error::{Result, RimgError};
-use crate::image::{Frame, ImageSequence, load_image};
+use crate::html::HtmlWriter;
+use crate::image::{Frame, ImageSequence, LoadOptions};
+use crate::interrupt::InterruptWatcher;
+use crate::prefetch::Prefetch;
+use crate::raster::{DEFAULT_CELL_SIZE, rasterize_frame, write_png};
+use crate::resize::ResizeWatcher;
+use crate::tonemap::ToneMapping;
 use crate::tmux;
 
-pub struct Renderer {
+/// How often a slideshow wait checks for terminal resizes
+const RESIZE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// Images decoded ahead of the display for each decoding thread
+const PREFETCH_PER_THREAD: usize = 2;
+
+type Sink<'a> = Box<dyn Write + Send + 'a>;
+
+pub struct Renderer<'a> {
     config: Config,
     backend: Box<dyn Backend + Send + Sync>,
-    terminal: crate::capabilities::TerminalSize,
+    terminal: Mutex<TerminalSize>,
     background: BackgroundStyle,
+    resize: ResizeWatcher,
+    interrupt: InterruptWatcher,
+    sink: Option<Mutex<Sink<'a>>>,
+    /// Virtual playback time while recording; waits advance it instead of sleeping
+    clock: Option<CastClock>,
 }
 
-impl Renderer {
-    pub fn build(config: Config) -> Result<Self> {
-        ensure_tty_stdout()?;
+/// Configures a [`Renderer`] beyond what [`Config`] describes
+///
+/// Everything left unset is detected from the environment exactly like
+/// [`Renderer::build`] does. Supplying an output sink lifts the TTY
+/// requirement; supplying a terminal size skips all terminal probing.
+///
+/// ```no_run
+/// # fn demo(config: showme::Config) -> showme::Result<()> {
+/// use showme::{BackendKind, BackendFactory, Renderer, TerminalSize};
+///
+/// let mut buffer = Vec::new();
+/// Renderer::builder(config)
+///     .output(&mut buffer)
+///     .terminal_size(TerminalSize { columns: 80, rows: 24, width_pixels: None, height_pixels: None })
+///     .backend(BackendFactory::build(BackendKind::Unicode)?)
+///     .build()?
+///     .run()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct RendererBuilder<'a> {
+    config: Config,
+    sink: Option<Sink<'a>>,
+    terminal: Option<TerminalSize>,
+    backend: Option<Box<dyn Backend + Send + Sync>>,
+}
 
-        let terminal = current_terminal_size();
-        let guess = detect_terminal_backend();
+impl<'a> RendererBuilder<'a> {
+    /// Write all output to `sink` instead of stdout or `Config::output_file`
+    ///
+    /// Cursor hiding and the alternate screen are skipped, since they only
+    /// make sense on the controlling terminal.
+    pub fn output(mut self, sink: impl Write + Send + 'a) -> Self {
+        self.sink = Some(Box::new(sink));
+        self
+    }
 
-        let backend_kind = match config.backend {
-            BackendKind::Auto => guess.backend,
-            other => other,
-        };
+    /// Lay images out for `terminal` instead of querying the real terminal
+    pub fn terminal_size(mut self, terminal: TerminalSize) -> Self {
+        self.terminal = Some(terminal);
+        self
+    }
+
+    /// Render with `backend`, ignoring `Config::backend`
+    pub fn backend(mut self, backend: Box<dyn Backend + Send + Sync>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    pub fn build(self) -> Result<Renderer<'a>> {
+        let RendererBuilder {
+            mut config,
+            sink,
+            terminal,
+            backend,
+        } = self;
+
+        // Pages and pictures are snapshots: first frames only, no pacing and no terminal state changes
+        let html = config.output_format == OutputFormat::Html;
+        let png = config.output_format == OutputFormat::Png;
+        let snapshot = html || png;
+        if snapshot {
+            config.max_frames = Some(1);
+            config.scroll_animation = false;
+            config.wait_between_images = None;
+            config.wait_between_rows = None;
+            config.clear_once = false;
+            config.clear_between = false;
+            config.alternate_screen = false;
+            config.hide_cursor = false;
+        }
+
+        // A recording replaces drawing, so terminal state is left alone and endless loops play once
+        let recording = config.record.is_some();
+        if recording {
+            if config.output_format != OutputFormat::Ansi {
+                return Err(RimgError::other("--record only works with ansi output"));
+            }
+            if sink.is_some() || config.output_file.is_some() {
+                return Err(RimgError::other("--record writes its own file and can't be combined with an output"));
+            }
+            config.alternate_screen = false;
+            config.hide_cursor = false;
+            if config.loop_forever && config.max_duration.is_none() {
+                config.loop_forever = false;
+            }
+        }
+
+        // Escape sequences are only refused when they would land on a redirected stdout
+        if sink.is_none() && config.output_file.is_none() && !snapshot && !recording {
+            ensure_tty_stdout()?;
+        }
+        if png && sink.is_none() && config.output_file.is_none() && io::stdout().is_terminal() {
+            return Err(RimgError::other(
+                "refusing to write PNG data to a terminal; redirect stdout or use --output",
+            ));
+        }
 
-        let backend = match BackendFactory::build(backend_kind) {
-            Ok(backend) => backend,
-            Err(err) => {
-                if !matches!(backend_kind, BackendKind::Unicode) && !config.quiet {
-                    eprintln!("warning: {} (falling back to unicode renderer)", err);
+        let terminal = terminal.or_else(|| fixed_terminal_size(config.term_size, config.cell_size));
+        let fixed_terminal = terminal.is_some();
+        let terminal = terminal.unwrap_or_else(current_terminal_size);
+
+        let backend = match backend {
+            Some(backend) => backend,
+            None => {
+                let backend_kind = match config.backend {
+                    BackendKind::Auto => detect_terminal_backend().backend,
+                    other => other,
+                };
+                match BackendFactory::build(backend_kind) {
+                    Ok(backend) => backend,
+                    Err(err) => {
+                        if !matches!(backend_kind, BackendKind::Unicode) && !config.quiet {
+                            eprintln!("warning: {} (falling back to unicode renderer)", err);
+                        }
+                        BackendFactory::auto_from_guess(BackendKind::Unicode)
+                    }
                 }
-                BackendFactory::auto_from_guess(BackendKind::Unicode)
             }
         };
+        let backend_kind = backend.supported_kind();
+
+        // Graphics protocols have no HTML form, so their images are embedded as <img>
+        // PNG export pictures the Unicode block art, so it always renders with that backend
+        let backend: Box<dyn Backend + Send + Sync> = if html && backend_kind != BackendKind::Unicode {
+            Box::new(HtmlImageBackend::new(backend_kind))
+        } else if png && backend_kind != BackendKind::Unicode {
+            if !matches!(config.backend, BackendKind::Auto) && !config.quiet {
+                eprintln!("warning: png output always uses the unicode renderer");
+            }
+            Box::new(UnicodeBackend)
+        } else {
+            backend
+        };
 
         // Enable tmux passthrough if using graphics protocols in tmux
-        if matches!(backend_kind, BackendKind::Kitty | BackendKind::Iterm2) && tmux::in_tmux() {
+        if sink.is_none() && !snapshot && !recording && matches!(backend_kind, BackendKind::Kitty | BackendKind::Iterm2) && tmux::in_tmux() {
             if tmux::enable_tmux_passthrough() && !config.quiet {
                 eprintln!("Enabled tmux passthrough for graphics protocol");
             }
@@ -83,30 +216,77 @@ impl Renderer {
             eprintln!();
         }
 
-        Ok(Self {
+        // Re-layout on resize only makes sense when drawing to the terminal itself
+        let resize = if sink.is_none() && config.output_file.is_none() && !fixed_terminal && !snapshot && !recording {
+            ResizeWatcher::install()
+        } else {
+            ResizeWatcher::disabled()
+        };
+
+        Ok(Renderer {
             config,
             backend,
-            terminal,
+            terminal: Mutex::new(terminal),
             background,
+            resize,
+            interrupt: InterruptWatcher::new(),
+            sink: sink.map(Mutex::new),
+            clock: recording.then(CastClock::new),
         })
     }
+}
+
+impl<'a> Renderer<'a> {
+    /// Build a renderer for the current terminal, writing to stdout or `Config::output_file`
+    pub fn build(config: Config) -> Result<Self> {
+        Self::builder(config).build()
+    }
+
+    /// Start configuring a renderer with an explicit sink, terminal size or backend
+    pub fn builder(config: Config) -> RendererBuilder<'a> {
+        RendererBuilder {
+            config,
+            sink: None,
+            terminal: None,
+            backend: None,
+        }
+    }
 
     pub fn run(&self) -> Result<()> {
-        let _alternate_guard = if self.config.alternate_screen {
+        // Catch Ctrl-C and friends so the guards below can restore the terminal
+        let _signals = self.interrupt.register();
+
+        // Terminal state guards act on the real stdout, never on a caller's sink
+        let _alternate_guard = if self.config.alternate_screen && self.sink.is_none() {
             Some(AlternateScreenGuard::enter()?)
         } else {
             None
         };
 
-        let sequences = self.load_sequences()?;
-        if sequences.is_empty() {
-            return Err(RimgError::MissingInput);
+        let mut images = self.prefetch()?;
+        // Nothing is written before the first image decoded, so a bad first input leaves no output behind
+        match images.peek() {
+            None => return Err(RimgError::MissingInput),
+            Some(Err(_)) => return Err(images.next().and_then(Result::err).unwrap_or(RimgError::MissingInput)),
+            Some(Ok(_)) => {}
         }
 
-        // Open output file if specified, otherwise use stdout
+        // Use the recording, the caller's sink or the output file if specified (the builder allows
+        // only one), otherwise stdout
+        let mut sink_output;
         let mut file_output;
         let mut stdout_output;
-        let output: &mut dyn Write = if let Some(ref path) = self.config.output_file {
+        let output: &mut dyn Write = if let Some(ref path) = self.config.record {
+            file_output = std::fs::File::create(path).map_err(|err| {
+                RimgError::other(format!("failed to create recording '{}': {}", path.display(), err))
+            })?;
+            &mut file_output
+        } else if let Some(sink) = &self.sink {
+            sink_output = sink
+                .lock()
+                .map_err(|_| RimgError::other("output sink is poisoned by an earlier panic"))?;
+            &mut **sink_output
+        } else if let Some(ref path) = self.config.output_file {
             file_output = std::fs::File::create(path).map_err(|err| {
                 RimgError::other(format!("failed to create output file '{}': {}", path.display(), err))
             })?;
@@ -117,31 +297,66 @@ impl Renderer {
         };
 
         // Hide cursor unless explicitly told not to
-        let _cursor_guard = if !self.config.hide_cursor {
+        let _cursor_guard = if !self.config.hide_cursor || self.sink.is_some() {
             None
         } else {
             Some(CursorHideGuard::hide()?)
         };
 
+        let result = match self.config.output_format {
+            OutputFormat::Ansi if self.clock.is_some() => self.record_all(&mut images, output),
+            OutputFormat::Ansi => {
+                let result = self.render_all(&mut images, output);
+                if matches!(result, Err(RimgError::Interrupted { .. })) {
+                    self.write_interrupt_cleanup(output);
+                }
+                result
+            }
+            OutputFormat::Html => {
+                let mut page = HtmlWriter::new(&mut *output);
+                self.render_all(&mut images, &mut page).and_then(|()| page.finish())
+            }
+            OutputFormat::Png => self.render_png(&mut images, output),
+        };
+        result?;
+
+        output.flush()?;
+
+        if self.config.verbose {
+            let stats = images.stats();
+            eprintln!("Loading statistics:");
+            eprintln!("  Images loaded: {}", stats.images);
+            eprintln!("  Total frames: {}", stats.frames);
+            eprintln!("  Decode time: {:.2}s (summed over threads)", stats.decode_time.as_secs_f64());
+            if stats.images > 0 {
+                eprintln!(
+                    "  Average: {:.0}ms per image",
+                    stats.decode_time.as_millis() as f64 / stats.images as f64
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Show images as they come out of the pipeline, dropping each once it has been shown
+    fn render_all(&self, images: &mut Prefetch, output: &mut dyn Write) -> Result<()> {
         if self.config.clear_once {
             output.write_all(b"\x1b[2J\x1b[H")?;
         }
 
         if let Some(grid) = &self.config.grid {
-            self.render_grid(grid, &sequences, output)?;
+            self.render_grid(grid, images, output)?;
         } else {
-            for (idx, sequence) in sequences.iter().enumerate() {
+            let mut idx = 0;
+            while let Some(sequence) = images.next() {
+                self.interrupt.check()?;
+                let sequence = sequence?;
+
                 if self.config.clear_between && (idx > 0 || !self.config.clear_once) {
                     output.write_all(b"\x1b[2J\x1b[H")?;
                 }
 
-                if !self.config.quiet {
-                    if let Some(title) = self.make_title(idx, sequence) {
-                        writeln!(output, "{title}")?;
-                    } else {
-                        writeln!(output, "# {} - {}", idx + 1, sequence.path.display())?;
-                    }
-                }
+                self.write_header(idx, &sequence, output)?;
 
                 if self.config.verbose {
                     if let Some(frame) = sequence.first_frame() {
@@ -154,79 +369,279 @@ impl Renderer {
                     }
                 }
 
-                self.render_sequence(sequence, output)?;
+                let drawn_height = self.render_sequence(&sequence, output)?;
 
                 if let Some(wait) = self.config.wait_between_images {
-                    if idx + 1 < sequences.len() {
-                        thread::sleep(wait);
+                    if images.peek().is_some() {
+                        self.hold_sequence(idx, &sequence, drawn_height, wait, output)?;
                     }
                 }
+                idx += 1;
             }
         }
 
-        output.flush()?;
         Ok(())
     }
 
-    fn load_sequences(&self) -> Result<Vec<ImageSequence>> {
-        let start = std::time::Instant::now();
-        let rotation_mode = self.config.rotation;
-        let auto_crop = self.config.auto_crop;
-        let crop_border = self.config.crop_border;
-
-        // Configure thread pool if specified
-        let sequences: Result<Vec<ImageSequence>> = if let Some(num_threads) = self.config.threads {
-            rayon::ThreadPoolBuilder::new()
-                .num_threads(num_threads)
-                .build()
-                .map_err(|e| RimgError::other(format!("failed to create thread pool: {}", e)))?
-                .install(|| {
-                    self.config
-                        .inputs
-                        .par_iter()
-                        .map(|path| load_image(path, rotation_mode, auto_crop, crop_border))
-                        .collect()
-                })
-        } else {
-            self.config
-                .inputs
-                .par_iter()
-                .map(|path| load_image(path, rotation_mode, auto_crop, crop_border))
-                .collect()
-        };
+    /// Render into an asciicast stream timed by the virtual clock
+    fn record_all(&self, images: &mut Prefetch, output: &mut dyn Write) -> Result<()> {
+        let clock = self.clock.clone().unwrap_or_default();
+        let terminal = self.terminal();
+        let mut cast = CastWriter::new(output, terminal.columns, terminal.rows, clock)?;
+        let result = self.render_all(images, &mut cast);
+        if matches!(result, Err(RimgError::Interrupted { .. })) {
+            self.write_interrupt_cleanup(&mut cast);
+        }
+        let finished = cast.finish();
+        result.and(finished)
+    }
 
-        let sequences = sequences?;
+    /// Wait for `delay`, or just advance the clock when recording
+    fn pause(&self, delay: Duration) -> Result<()> {
+        match &self.clock {
+            Some(clock) => {
+                self.interrupt.check()?;
+                clock.advance(delay);
+                Ok(())
+            }
+            None => self.interrupt.sleep(delay),
+        }
+    }
 
-        if self.config.verbose {
-            let elapsed = start.elapsed();
-            let total_frames: usize = sequences.iter().map(|s| s.frames.len()).sum();
-            eprintln!("Loading statistics:");
-            eprintln!("  Images loaded: {}", sequences.len());
-            eprintln!("  Total frames: {}", total_frames);
-            eprintln!("  Load time: {:.2}s", elapsed.as_secs_f64());
-            if sequences.len() > 0 {
-                eprintln!("  Average: {:.0}ms per image", elapsed.as_millis() as f64 / sequences.len() as f64);
+    /// Time since `started`, measured on the recording clock when there is one
+    fn elapsed_since(&self, started: (Instant, Duration)) -> Duration {
+        match &self.clock {
+            Some(clock) => clock.elapsed().saturating_sub(started.1),
+            None => started.0.elapsed(),
+        }
+    }
+
+    /// Rasterise the first frame of every sequence and stack them into one PNG
+    ///
+    /// Images are separated by one empty cell row; titles and grid layout
+    /// are not part of the picture.
+    fn render_png(&self, images: &mut Prefetch, output: &mut dyn Write) -> Result<()> {
+        let (cell_width, cell_height) = self
+            .config
+            .cell_size
+            .map_or(DEFAULT_CELL_SIZE, |(width, height)| (u32::from(width), u32::from(height)));
+        let options = self.render_options();
+
+        let mut pictures = Vec::new();
+        for sequence in images {
+            self.interrupt.check()?;
+            let sequence = sequence?;
+            let start_frame = self.config.frame_offset.min(sequence.frames.len().saturating_sub(1));
+            if let Some(frame) = sequence.frames.get(start_frame) {
+                let rendered = self.backend.render(frame, options)?;
+                pictures.push(rasterize_frame(&rendered, cell_width, cell_height));
             }
-            eprintln!();
         }
 
-        Ok(sequences)
+        let width = pictures.iter().map(|picture| picture.width()).max().unwrap_or(0);
+        let gaps = cell_height * pictures.len().saturating_sub(1) as u32;
+        let height = pictures.iter().map(|picture| picture.height()).sum::<u32>() + gaps;
+        let mut sheet = image::RgbaImage::new(width.max(1), height.max(1));
+        let mut y = 0;
+        for picture in &pictures {
+            image::imageops::replace(&mut sheet, picture, 0, i64::from(y));
+            y += picture.height() + cell_height;
+        }
+        write_png(&sheet, output)
+    }
+
+    /// Leave the terminal in a usable state after playback was cut short
+    fn write_interrupt_cleanup(&self, output: &mut dyn Write) {
+        let _ = output.write_all(b"\x1b[0m");
+        for line in self.backend.cleanup() {
+            let _ = output.write_all(line.as_bytes());
+        }
+        let _ = output.write_all(b"\n");
+        let _ = output.flush();
+    }
+
+    /// Decoding options for the configuration and current terminal
+    fn load_options(&self) -> LoadOptions {
+        let (width, height) = self.raster_size();
+        let mut options = LoadOptions::new(self.config.rotation, self.config.auto_crop, self.config.crop_border)
+            .with_auto_crop_tolerance(self.config.auto_crop_tolerance)
+            .with_flip(self.config.flip)
+            .with_crop(self.config.crop)
+            .with_raster_size(width, height)
+            .with_limits(self.config.decode_limits)
+            .with_tone_mapping(ToneMapping {
+                operator: self.config.tone_map,
+                exposure: self.config.exposure,
+            })
+            .with_color_management(self.config.color_management)
+            .with_adjustments(Adjustments {
+                brightness: self.config.brightness,
+                contrast: self.config.contrast,
+                gamma: self.config.gamma,
+                saturation: self.config.saturation,
+                grayscale: self.config.grayscale,
+                invert: self.config.invert,
+                // Backends that scale sharpen the scaled image instead
+                sharpen: self.config.sharpen.filter(|_| !self.backend.scales_images()),
+            });
+        // Grid cells are small, so thumbnails are decoded instead of full images
+        if let Some(grid) = &self.config.grid {
+            let (width, height) = self.grid_cell_pixels(grid);
+            options = options.with_target_size(width, height);
+        }
+        options
+    }
+
+    /// Pixel box the sized image can fill on this terminal, which PDF pages and SVGs are rendered to
+    ///
+    /// Uses the reported window size when there is one and the configured
+    /// (or default) cell size otherwise. A fitted dimension is left unbounded.
+    fn raster_size(&self) -> (u32, u32) {
+        let terminal = self.terminal();
+        let (cell_width, cell_height) = match (terminal.width_pixels, terminal.height_pixels) {
+            (Some(width), Some(height)) if terminal.columns > 0 && terminal.rows > 0 => (
+                (u32::from(width) / u32::from(terminal.columns)).max(1),
+                (u32::from(height) / u32::from(terminal.rows)).max(1),
+            ),
+            _ => self
+                .config
+                .cell_size
+                .map_or(DEFAULT_CELL_SIZE, |(width, height)| (u32::from(width), u32::from(height))),
+        };
+        let sizing = &self.config.sizing;
+        let columns = sizing.width_cells.unwrap_or(u32::from(terminal.columns)).max(1);
+        let rows = sizing.height_cells.unwrap_or(u32::from(terminal.rows)).max(1);
+        let width = if sizing.fit_height { u32::MAX } else { columns.saturating_mul(cell_width) };
+        let height = if sizing.fit_width { u32::MAX } else { rows.saturating_mul(cell_height) };
+        (width, height)
+    }
+
+    /// Decode the inputs in order, a few ahead of the one on screen
+    fn prefetch(&self) -> Result<Prefetch> {
+        let mut threads = rayon::current_num_threads();
+        let mut pool = None;
+        if let Some(num_threads) = self.config.threads {
+            threads = num_threads;
+            pool = Some(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .build()
+                    .map_err(|e| RimgError::other(format!("failed to create thread pool: {}", e)))?,
+            );
+        }
+        // Keep every thread busy, and a whole grid row decoding together
+        let columns = self.config.grid.as_ref().map_or(1, |grid| grid.columns.get());
+        let depth = (threads * PREFETCH_PER_THREAD).max(columns);
+
+        let prefetch = Prefetch::new(self.config.inputs.clone(), self.load_options(), depth)
+            .with_pages(self.config.pages.clone())
+            .with_interrupt(self.interrupt.clone());
+        Ok(match pool {
+            Some(pool) => prefetch.with_pool(pool),
+            None => prefetch,
+        })
+    }
+
+    fn write_header(&self, idx: usize, sequence: &ImageSequence, output: &mut dyn Write) -> Result<()> {
+        if self.config.quiet {
+            return Ok(());
+        }
+        if let Some(title) = self.make_title(idx, sequence) {
+            writeln!(output, "{title}")?;
+        } else if let Some((page, count)) = sequence.page {
+            writeln!(output, "# {} - {} (page {page}/{count})", idx + 1, sequence.path.display())?;
+        } else {
+            writeln!(output, "# {} - {}", idx + 1, sequence.path.display())?;
+        }
+        Ok(())
     }
 
-    fn render_sequence(&self, sequence: &ImageSequence, stdout: &mut dyn Write) -> Result<()> {
-        let options = RenderOptions {
+    fn render_options(&self) -> RenderOptions {
+        RenderOptions {
             sizing: self.config.sizing,
-            terminal: self.terminal,
+            terminal: self.terminal(),
             background: self.background,
             pixelation: self.config.pixelation,
             use_8bit_color: self.config.use_8bit_color,
             compress_level: self.config.compress_level,
             verbose: self.config.verbose,
-        };
+            sharpen: self.config.sharpen,
+        }
+    }
+
+    fn terminal(&self) -> TerminalSize {
+        match self.terminal.lock() {
+            Ok(terminal) => *terminal,
+            Err(poisoned) => *poisoned.into_inner(),
+        }
+    }
+
+    /// Refresh the cached terminal size if a resize happened since the last check
+    fn take_resize(&self) -> bool {
+        if !self.resize.take() {
+            return false;
+        }
+        let refreshed = current_terminal_size();
+        match self.terminal.lock() {
+            Ok(mut terminal) => *terminal = refreshed,
+            Err(poisoned) => *poisoned.into_inner() = refreshed,
+        }
+        if self.config.verbose {
+            eprintln!("Terminal resized to {}x{} cells", refreshed.columns, refreshed.rows);
+        }
+        true
+    }
+
+    /// Remove the previous drawing so it can be redrawn at the new size
+    fn clear_for_redraw(&self, drawn_lines: u32, stdout: &mut dyn Write) -> Result<()> {
+        if self.config.alternate_screen {
+            stdout.write_all(b"\x1b[2J\x1b[H")?;
+        } else if drawn_lines > 0 {
+            write!(stdout, "\x1b[{}A\x1b[J", drawn_lines)?;
+        }
+        Ok(())
+    }
+
+    /// Keep a static image on screen for `wait`, redrawing it whenever the terminal is resized
+    fn hold_sequence(
+        &self,
+        idx: usize,
+        sequence: &ImageSequence,
+        mut drawn_height: u32,
+        wait: Duration,
+        stdout: &mut dyn Write,
+    ) -> Result<()> {
+        if self.clock.is_some() {
+            return self.pause(wait);
+        }
+
+        let deadline = Instant::now() + wait;
+        let is_static = sequence.frames.len() == 1 && !self.config.scroll_animation;
+
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(());
+            }
+            self.interrupt.sleep((deadline - now).min(RESIZE_POLL_INTERVAL))?;
+
+            if is_static && self.take_resize() {
+                let header_lines = if self.config.quiet { 0 } else { 1 };
+                self.clear_for_redraw(drawn_height + header_lines, stdout)?;
+                self.write_header(idx, sequence, stdout)?;
+                drawn_height = self.render_sequence(sequence, stdout)?;
+            }
+        }
+    }
+
+    /// Render one sequence and return the height in cells of the last frame drawn
+    fn render_sequence(&self, sequence: &ImageSequence, stdout: &mut dyn Write) -> Result<u32> {
+        let mut options = self.render_options();
 
         // Handle scrolling animation mode
         if self.config.scroll_animation {
-            return self.render_scrolling(sequence, options, stdout);
+            self.render_scrolling(sequence, options, stdout)?;
+            return Ok(0);
         }
 
         // Apply frame offset and limit
@@ -240,13 +655,12 @@ impl Renderer {
         };
 
         if frames_to_render.is_empty() {
-            return Ok(());
+            return Ok(0);
         }
 
         // Single frame or static image
         if frames_to_render.len() == 1 {
-            self.print_frame(&frames_to_render[0], options, stdout)?;
-            return Ok(());
+            return self.print_frame(&frames_to_render[0], options, stdout);
         }
 
         // Determine loop behavior
@@ -261,18 +675,29 @@ impl Renderer {
         let mut first = true;
         let mut last_height = 0u32;
         let mut current_loop = 0i32;
-        let start_time = std::time::Instant::now();
+        let start_time = (Instant::now(), self.clock.as_ref().map(CastClock::elapsed).unwrap_or_default());
         let mut _total_frames_rendered = 0usize; // Reserved for future verbose output
 
         loop {
             for frame in frames_to_render {
+                self.interrupt.check()?;
+
                 // Check duration limit
                 if let Some(max_duration) = self.config.max_duration {
-                    if start_time.elapsed() >= max_duration {
-                        return Ok(());
+                    if self.elapsed_since(start_time) >= max_duration {
+                        return Ok(last_height);
                     }
                 }
 
+                // Re-scale to the new terminal size and redraw from a clean slate
+                if self.take_resize() {
+                    options.terminal = self.terminal();
+                    if !first {
+                        self.clear_for_redraw(last_height, stdout)?;
+                    }
+                    first = true;
+                }
+
                 let rendered = self.backend.render(frame, options)?;
 
                 if !first {
@@ -290,7 +715,7 @@ impl Renderer {
                 _total_frames_rendered += 1;
 
                 if frame.delay > Duration::ZERO {
-                    thread::sleep(frame.delay);
+                    self.pause(frame.delay)?;
                 }
             }
 
@@ -302,7 +727,7 @@ impl Renderer {
             }
         }
 
-        Ok(())
+        Ok(last_height)
     }
 
     fn render_scrolling(
@@ -332,8 +757,8 @@ impl Renderer {
 
         let image_width = rendered.width_cells as i32;
         let image_height = rendered.lines.len() as i32;
-        let viewport_width = self.terminal.columns as i32;
-        let viewport_height = self.terminal.rows as i32;
+        let viewport_width = options.terminal.columns as i32;
+        let viewport_height = options.terminal.rows as i32;
 
         // If image fits in viewport, no scrolling needed
         if image_width <= viewport_width && image_height <= viewport_height {
@@ -389,6 +814,8 @@ impl Renderer {
 
         loop {
             for (scroll_x, scroll_y) in &positions {
+                self.interrupt.check()?;
+
                 if !first {
                     // Move cursor back to start
                     if viewport_height > 0 {
@@ -420,7 +847,7 @@ impl Renderer {
 
                 // Delay between scroll steps
                 if self.config.scroll_delay > Duration::ZERO {
-                    thread::sleep(self.config.scroll_delay);
+                    self.pause(self.config.scroll_delay)?;
                 }
             }
 
@@ -440,12 +867,12 @@ impl Renderer {
         frame: &Frame,
         options: RenderOptions,
         stdout: &mut dyn Write,
-    ) -> Result<()> {
+    ) -> Result<u32> {
         let rendered = self.backend.render(frame, options)?;
         let indent = self.indent_for(&rendered);
-        let res = self.write_rendered(&rendered, indent, stdout)?;
+        self.write_rendered(&rendered, indent, stdout)?;
         stdout.flush()?;
-        Ok(res)
+        Ok(rendered.height_cells)
     }
 
     fn write_rendered(
@@ -470,10 +897,26 @@ impl Renderer {
         Ok(())
     }
 
+    /// Largest pixel size a unicode grid cell can show
+    ///
+    /// Quarter and half blocks pack at most 2×2 pixels into a character, and
+    /// cells are width-limited, so full rows bound the width and the terminal
+    /// height bounds the rest.
+    fn grid_cell_pixels(&self, grid: &crate::config::GridOptions) -> (u32, u32) {
+        let terminal = self.terminal();
+        let columns = grid.columns.get();
+        let gaps = (grid.spacing as usize).saturating_mul(columns.saturating_sub(1));
+        let mut per_column = ((terminal.columns as usize).saturating_sub(gaps) / columns).max(1) as u32;
+        if let Some(limit) = self.config.sizing.width_cells {
+            per_column = per_column.min(limit);
+        }
+        (per_column * 2, u32::from(terminal.rows) * 2)
+    }
+
     fn render_grid(
         &self,
         grid: &crate::config::GridOptions,
-        sequences: &[ImageSequence],
+        images: &mut Prefetch,
         stdout: &mut dyn Write,
     ) -> Result<()> {
         if self.backend.supported_kind() != BackendKind::Unicode {
@@ -490,21 +933,24 @@ impl Renderer {
         let max_rows = grid.rows.map(|r| r.get()).unwrap_or(usize::MAX);
         let gap = " ".repeat(grid.spacing as usize);
 
-        for (row_index, chunk) in sequences.chunks(columns).into_iter().enumerate() {
-            if row_index >= max_rows {
+        for row_index in 0..max_rows {
+            // The pipeline decodes a row's images side by side
+            let row = images.by_ref().take(columns).collect::<Result<Vec<_>>>()?;
+            if row.is_empty() {
                 break;
             }
 
-            let effective_columns = chunk.len();
+            let effective_columns = row.len();
 
             // Use actual terminal width for grid allocation
-            let available_cells = self.terminal.columns as usize;
+            let terminal = self.terminal();
+            let available_cells = terminal.columns as usize;
             let reserved_for_gaps =
                 (grid.spacing as usize).saturating_mul(effective_columns.saturating_sub(1));
             let per_column =
                 available_cells.saturating_sub(reserved_for_gaps).max(1) / effective_columns.max(1);
 
-            let rendered: Vec<_> = chunk
+            let rendered: Vec<_> = row
                 .iter()
                 .map(|sequence| {
                     sequence
@@ -520,12 +966,13 @@ impl Renderer {
                             sizing.width_cells = Some(width_override);
                             let options = RenderOptions {
                                 sizing,
-                                terminal: self.terminal,
+                                terminal,
                                 background: self.background,
                                 pixelation: self.config.pixelation,
                                 use_8bit_color: self.config.use_8bit_color,
                                 compress_level: self.config.compress_level,
                                 verbose: self.config.verbose,
+                                sharpen: self.config.sharpen,
                             };
                             self.backend.render(frame, options)
                         })
@@ -561,10 +1008,9 @@ impl Renderer {
             stdout.write_all(b"\n")?;
 
             if let Some(wait) = self.config.wait_between_rows {
-                let more_rows_available =
-                    (row_index + 1) < max_rows && (row_index + 1) * columns < sequences.len();
+                let more_rows_available = (row_index + 1) < max_rows && images.peek().is_some();
                 if more_rows_available {
-                    thread::sleep(wait);
+                    self.pause(wait)?;
                 }
             }
         }
@@ -577,7 +1023,7 @@ impl Renderer {
             return 0;
         }
 
-        let available = self.terminal.columns as isize - rendered.width_cells as isize;
+        let available = self.terminal().columns as isize - rendered.width_cells as isize;
         if available > 0 {
             (available / 2) as usize
         } else {
@@ -608,6 +1054,11 @@ impl Renderer {
                     Some('w') => out.push_str(&width.to_string()),
                     Some('h') => out.push_str(&height.to_string()),
                     Some('n') => out.push_str(&(index + 1).to_string()),
+                    Some('p') => {
+                        if let Some((page, _)) = sequence.page {
+                            out.push_str(&page.to_string());
+                        }
+                    }
                     Some('%') => out.push('%'),
                     Some(other) => {
                         out.push('%');
@@ -674,3 +1125,179 @@ impl Drop for CursorHideGuard {
         let _ = stdout.flush();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Cli;
+    use crate::preferences::Preferences;
+    use crate::test_util::TempPath;
+    use clap::Parser;
+
+    #[test]
+    fn renders_into_a_buffer_without_a_tty() {
+        let path = TempPath::new("renderer.png");
+        image::RgbaImage::from_pixel(8, 8, image::Rgba([255, 0, 0, 255]))
+            .save(&path)
+            .expect("write png");
+
+        let config = Cli::try_parse_from(["showme", "--no-config", path.to_str().unwrap()])
+            .expect("args parse")
+            .into_config_with(Preferences::default())
+            .expect("config");
+
+        let mut buffer = Vec::new();
+        Renderer::builder(config)
+            .output(&mut buffer)
+            .terminal_size(TerminalSize {
+                columns: 20,
+                rows: 10,
+                width_pixels: None,
+                height_pixels: None,
+            })
+            .backend(BackendFactory::build(BackendKind::Unicode).expect("unicode backend"))
+            .build()
+            .expect("renderer builds")
+            .run()
+            .expect("renders");
+
+        let output = String::from_utf8(buffer).expect("utf-8 output");
+        assert!(output.starts_with(&format!("# 1 - {}\n", path.display())));
+        assert!(output.contains("\x1b[38;2;255;0;0m") || output.contains("\x1b[48;2;255;0;0m"));
+        assert!(!output.contains("\x1b[?25l"));
+    }
+
+    #[test]
+    fn html_output_is_a_standalone_page() {
+        let path = TempPath::new("renderer_html.png");
+        image::RgbaImage::from_pixel(4, 4, image::Rgba([0, 0, 255, 255]))
+            .save(&path)
+            .expect("write png");
+
+        let config = Cli::try_parse_from([
+            "showme",
+            "--no-config",
+            "--output-format",
+            "html",
+            "--title",
+            "<%b>",
+            path.to_str().unwrap(),
+        ])
+        .expect("args parse")
+        .into_config_with(Preferences::default())
+        .expect("config");
+
+        let mut buffer = Vec::new();
+        Renderer::builder(config)
+            .output(&mut buffer)
+            .terminal_size(TerminalSize {
+                columns: 20,
+                rows: 10,
+                width_pixels: None,
+                height_pixels: None,
+            })
+            .backend(Box::new(UnicodeBackend::default()))
+            .build()
+            .expect("renderer builds")
+            .run()
+            .expect("renders");
+
+        let page = String::from_utf8(buffer).expect("utf-8 output");
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains(&format!("&lt;{}&gt;", path.file_name().unwrap().to_string_lossy())));
+        assert!(page.contains("background-color:#0000ff;") || page.contains("color:#0000ff;"));
+        assert!(!page.contains('\x1b'));
+    }
+
+    #[test]
+    fn png_output_pictures_the_block_art() {
+        let path = TempPath::new("renderer_png.png");
+        image::RgbaImage::from_pixel(4, 4, image::Rgba([0, 0, 255, 255]))
+            .save(&path)
+            .expect("write png");
+
+        let config = Cli::try_parse_from([
+            "showme",
+            "--no-config",
+            "--output-format",
+            "png",
+            "--term-size",
+            "20x10",
+            "--cell-size",
+            "2x4",
+            "--backend",
+            "kitty",
+            path.to_str().unwrap(),
+        ])
+        .expect("args parse")
+        .into_config_with(Preferences::default())
+        .expect("config");
+
+        let mut buffer = Vec::new();
+        Renderer::builder(config)
+            .output(&mut buffer)
+            .build()
+            .expect("renderer builds")
+            .run()
+            .expect("renders");
+
+        let picture = image::load_from_memory(&buffer).expect("png output").to_rgba8();
+        assert_eq!(picture.height(), 2 * 4);
+        assert_eq!(*picture.get_pixel(0, 0), image::Rgba([0, 0, 255, 255]));
+        assert_eq!(*picture.get_pixel(0, 7), image::Rgba([0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn records_animation_with_frame_delay_timestamps() {
+        let path = TempPath::new("renderer_cast.gif");
+        let cast = TempPath::new("renderer_cast.cast");
+        {
+            let file = std::fs::File::create(&path).expect("create gif");
+            let mut encoder = image::codecs::gif::GifEncoder::new(file);
+            for color in [[255, 0, 0, 255], [0, 255, 0, 255]] {
+                let pixels = image::RgbaImage::from_pixel(4, 4, image::Rgba(color));
+                let delay = image::Delay::from_numer_denom_ms(250, 1);
+                encoder
+                    .encode_frame(image::Frame::from_parts(pixels, 0, 0, delay))
+                    .expect("encode frame");
+            }
+        }
+
+        let config = Cli::try_parse_from([
+            "showme",
+            "--no-config",
+            "--backend",
+            "unicode",
+            "--term-size",
+            "20x10",
+            "--record",
+            cast.to_str().unwrap(),
+            path.to_str().unwrap(),
+        ])
+        .expect("args parse")
+        .into_config_with(Preferences::default())
+        .expect("config");
+
+        Renderer::build(config)
+            .expect("renderer builds")
+            .run()
+            .expect("records");
+        let recording = std::fs::read_to_string(&cast).expect("read recording");
+
+        let lines: Vec<_> = recording.lines().collect();
+        assert!(lines[0].starts_with("{\"version\": 2, \"width\": 20, \"height\": 10, "));
+        assert!(lines[1].starts_with("[0.000000, \"o\", "));
+        assert!(lines[1].contains("\\u001b[38;2;255;0;0m") || lines[1].contains("\\u001b[48;2;255;0;0m"));
+        assert!(lines[2].starts_with("[0.250000, \"o\", "));
+        assert_eq!(lines.len(), 3);
+
+        // The recording is the only output, so a sink can't be given too
+        let config = Cli::try_parse_from(["showme", "--no-config", "--record", "out.cast", "img.png"])
+            .expect("args parse")
+            .into_config_with(Preferences::default())
+            .expect("config");
+        let err = Renderer::builder(config).output(Vec::new()).build().err().expect("sink refused");
+        assert!(err.to_string().contains("--record"));
+        assert!(Cli::try_parse_from(["showme", "--record", "out.cast", "-o", "out.txt", "img.png"]).is_err());
+    }
+}
@@ -0,0 +1,104 @@
+//! Terminal resize detection used during playback
+//!
+//! On Unix the watcher registers a SIGWINCH handler that raises a flag, so
+//! checking for a resize between frames costs a single atomic swap. Other
+//! platforms have no resize signal, so the watcher falls back to comparing
+//! the current terminal size against the last one it saw.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(not(unix))]
+use std::sync::Mutex;
+
+pub struct ResizeWatcher {
+    pending: Arc<AtomicBool>,
+    #[cfg(unix)]
+    signal_id: Option<signal_hook::SigId>,
+    #[cfg(not(unix))]
+    last_size: Mutex<Option<(u16, u16)>>,
+}
+
+impl ResizeWatcher {
+    /// Start watching for terminal resizes
+    ///
+    /// If the signal handler cannot be installed the watcher never reports
+    /// a resize, which matches the previous fixed-size behaviour.
+    #[cfg(unix)]
+    pub fn install() -> Self {
+        let pending = Arc::new(AtomicBool::new(false));
+        let signal_id =
+            signal_hook::flag::register(signal_hook::consts::SIGWINCH, Arc::clone(&pending)).ok();
+        Self { pending, signal_id }
+    }
+
+    #[cfg(not(unix))]
+    pub fn install() -> Self {
+        Self {
+            pending: Arc::new(AtomicBool::new(false)),
+            last_size: Mutex::new(crossterm::terminal::size().ok()),
+        }
+    }
+
+    /// A watcher that never reports a resize (used when output is not a terminal)
+    pub fn disabled() -> Self {
+        Self {
+            pending: Arc::new(AtomicBool::new(false)),
+            #[cfg(unix)]
+            signal_id: None,
+            #[cfg(not(unix))]
+            last_size: Mutex::new(None),
+        }
+    }
+
+    /// Returns true once for every resize observed since the last call
+    pub fn take(&self) -> bool {
+        #[cfg(not(unix))]
+        self.poll_size();
+
+        self.pending.swap(false, Ordering::Relaxed)
+    }
+
+    #[cfg(not(unix))]
+    fn poll_size(&self) {
+        let Ok(mut last) = self.last_size.lock() else {
+            return;
+        };
+        let Some(previous) = *last else {
+            return;
+        };
+        if let Ok(current) = crossterm::terminal::size() {
+            if current != previous {
+                *last = Some(current);
+                self.pending.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for ResizeWatcher {
+    fn drop(&mut self) {
+        if let Some(id) = self.signal_id.take() {
+            signal_hook::low_level::unregister(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_watcher_never_reports_resize() {
+        let watcher = ResizeWatcher::disabled();
+        assert!(!watcher.take());
+    }
+
+    #[test]
+    fn pending_flag_is_cleared_after_take() {
+        let watcher = ResizeWatcher::disabled();
+        watcher.pending.store(true, Ordering::Relaxed);
+        assert!(watcher.take());
+        assert!(!watcher.take());
+    }
+}
@@ -0,0 +1,129 @@
+//! Tone mapping of high dynamic range images
+//!
+//! EXR and Radiance HDR files decode to linear floating-point samples that
+//! routinely exceed 1.0. Converting them straight to 8 bits clips every
+//! highlight to white, so float images are first scaled by the exposure,
+//! compressed into `[0, 1]` by a tone-mapping operator and then encoded with
+//! the sRGB transfer curve. Integer images pass through untouched.
+
+use image::{DynamicImage, Rgba, Rgba32FImage, RgbaImage};
+
+use crate::config::ToneMapOperator;
+
+/// How float images are brought into displayable range
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ToneMapping {
+    pub operator: ToneMapOperator,
+    /// Exposure adjustment in stops, applied before the operator
+    pub exposure: f32,
+}
+
+/// Whether `image` holds floating-point samples that need tone mapping
+pub fn is_hdr(image: &DynamicImage) -> bool {
+    matches!(image, DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_))
+}
+
+/// Tone map a float image into 8-bit sRGB; other images are returned as-is
+pub fn apply(image: DynamicImage, mapping: &ToneMapping) -> DynamicImage {
+    if !is_hdr(&image) {
+        return image;
+    }
+    DynamicImage::ImageRgba8(tone_map(&image.into_rgba32f(), mapping))
+}
+
+fn tone_map(image: &Rgba32FImage, mapping: &ToneMapping) -> RgbaImage {
+    let gain = mapping.exposure.exp2();
+    let mut out = RgbaImage::new(image.width(), image.height());
+    for (mapped, pixel) in out.pixels_mut().zip(image.pixels()) {
+        let [r, g, b, a] = pixel.0;
+        let rgb = [r, g, b].map(|c| if c.is_finite() { c.max(0.0) * gain } else { 0.0 });
+        let rgb = match mapping.operator {
+            ToneMapOperator::Clip => rgb,
+            ToneMapOperator::Reinhard => reinhard(rgb),
+            ToneMapOperator::Aces => rgb.map(aces),
+            ToneMapOperator::Hable => rgb.map(hable),
+        };
+        let [r, g, b] = rgb.map(encode_srgb);
+        *mapped = Rgba([r, g, b, (a.clamp(0.0, 1.0) * 255.0).round() as u8]);
+    }
+    out
+}
+
+/// Reinhard on luminance, so saturated highlights keep their hue
+fn reinhard(rgb: [f32; 3]) -> [f32; 3] {
+    let luminance = 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2];
+    if luminance <= 0.0 {
+        return rgb;
+    }
+    let scale = 1.0 / (1.0 + luminance);
+    rgb.map(|c| c * scale)
+}
+
+/// Narkowicz's fit of the ACES filmic curve
+fn aces(x: f32) -> f32 {
+    let x = x * 0.6;
+    (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+}
+
+/// John Hable's Uncharted 2 curve with its usual exposure bias and white point
+fn hable(x: f32) -> f32 {
+    const WHITE: f32 = 11.2;
+    fn curve(x: f32) -> f32 {
+        const A: f32 = 0.15;
+        const B: f32 = 0.50;
+        const C: f32 = 0.10;
+        const D: f32 = 0.20;
+        const E: f32 = 0.02;
+        const F: f32 = 0.30;
+        (x * (A * x + C * B) + D * E) / (x * (A * x + B) + D * F) - E / F
+    }
+    curve(2.0 * x) / curve(WHITE)
+}
+
+fn encode_srgb(linear: f32) -> u8 {
+    let linear = linear.clamp(0.0, 1.0);
+    let encoded = if linear <= 0.003_130_8 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hdr(values: &[f32]) -> DynamicImage {
+        let mut image = Rgba32FImage::new(values.len() as u32, 1);
+        for (pixel, &value) in image.pixels_mut().zip(values) {
+            *pixel = Rgba([value, value, value, 1.0]);
+        }
+        DynamicImage::ImageRgba32F(image)
+    }
+
+    fn gray(image: &DynamicImage) -> Vec<u8> {
+        image.to_rgba8().pixels().map(|pixel| pixel[0]).collect()
+    }
+
+    #[test]
+    fn compresses_highlights_instead_of_clipping() {
+        let values = [0.0, 0.18, 1.0, 4.0, 16.0];
+        for operator in [ToneMapOperator::Reinhard, ToneMapOperator::Aces, ToneMapOperator::Hable] {
+            let mapped = gray(&apply(hdr(&values), &ToneMapping { operator, exposure: 0.0 }));
+            assert_eq!(mapped[0], 0, "{operator:?}");
+            assert!(mapped.windows(2).all(|pair| pair[0] < pair[1]), "{operator:?}: {mapped:?}");
+        }
+
+        let clipped = gray(&apply(hdr(&values), &ToneMapping { operator: ToneMapOperator::Clip, exposure: 0.0 }));
+        assert_eq!(clipped[2..], [255, 255, 255]);
+        let darker = gray(&apply(hdr(&[1.0]), &ToneMapping { operator: ToneMapOperator::Clip, exposure: -1.0 }));
+        assert_eq!(darker, [188]);
+    }
+
+    #[test]
+    fn leaves_integer_images_alone() {
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_pixel(1, 1, Rgba([10, 20, 30, 40])));
+        assert_eq!(apply(image.clone(), &ToneMapping::default()), image);
+    }
+}
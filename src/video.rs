@@ -1,11 +1,11 @@
 use std::path::Path;
 use std::sync::OnceLock;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use ffmpeg_next as ffmpeg;
 
 use crate::error::{Result, RimgError};
-use crate::image::{Frame, ImageSequence};
+use crate::image::{DecodeLimits, Frame, ImageSequence};
 
 static FFMPEG_INIT: OnceLock<()> = OnceLock::new();
 
@@ -32,7 +32,14 @@ impl VideoLoader {
 }
 
 pub fn load_video(path: &Path) -> Result<Option<ImageSequence>> {
+    load_video_frames(path, None, &DecodeLimits::default())
+}
+
+/// Decode at most `limit` frames of the video at `path`, failing if it exceeds `limits`
+pub fn load_video_frames(path: &Path, limit: Option<usize>, limits: &DecodeLimits) -> Result<Option<ImageSequence>> {
     VideoLoader::ensure_initialized();
+    let started = Instant::now();
+    let limit = limit.unwrap_or(usize::MAX);
 
     let mut input = match ffmpeg::format::input(&path) {
         Ok(ctx) => ctx,
@@ -59,6 +66,7 @@ pub fn load_video(path: &Path) -> Result<Option<ImageSequence>> {
 
     let target_width = decoder.width();
     let target_height = decoder.height();
+    limits.check_pixels(target_width, target_height, path)?;
 
     let mut scaler = ffmpeg::software::scaling::context::Context::get(
         decoder.format(),
@@ -76,6 +84,9 @@ pub fn load_video(path: &Path) -> Result<Option<ImageSequence>> {
     let mut last_pts: Option<i64> = None;
 
     for (stream, packet) in input.packets() {
+        if frames.len() >= limit {
+            break;
+        }
         if stream.index() == stream_index {
             decoder
                 .send_packet(&packet)
@@ -90,6 +101,8 @@ pub fn load_video(path: &Path) -> Result<Option<ImageSequence>> {
                 &mut last_pts,
                 &mut frames,
             )?;
+            limits.check_frames(frames.len().min(limit), path)?;
+            limits.check_time(started, path)?;
         }
     }
 
@@ -107,6 +120,8 @@ pub fn load_video(path: &Path) -> Result<Option<ImageSequence>> {
         &mut frames,
     )?;
 
+    frames.truncate(limit);
+    limits.check_frames(frames.len(), path)?;
     if frames.is_empty() {
         return Ok(None);
     }
@@ -114,6 +129,7 @@ pub fn load_video(path: &Path) -> Result<Option<ImageSequence>> {
     Ok(Some(ImageSequence {
         path: path.to_path_buf(),
         frames,
+        page: None,
     }))
 }
 